@@ -1,55 +1,642 @@
-use gfa2::gfa1::GFA;
-use gfa2::gfa2::GFA2;
-use handlegraph2::hashgraph::HashGraph;
+use crate::graphoperation::{
+    edge_key, to_patch_string, GFAGraph, GraphOperationError, PatchEntry, Walk,
+};
+use gfa2::tag::OptionalFields;
+use handlegraph2::{
+    handle::{Edge, Handle, NodeId},
+    handlegraph::HandleGraph,
+};
+
+use bstr::{BString, ByteSlice};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::hash::Hash;
+use std::io::{self, prelude::*, BufWriter};
+use std::path::{Path, PathBuf};
+
+fn orientation(handle: Handle) -> &'static str {
+    if handle.is_reverse() {
+        "-"
+    } else {
+        "+"
+    }
+}
+
+fn path_steps(graph: &GFAGraph, steps: &[Handle], separator: &str) -> String {
+    let steps: Vec<String> = steps
+        .iter()
+        .map(|step| graph.oriented_id(*step).to_string())
+        .collect();
+    steps.join(separator)
+}
+
+/// The order of the records of a saved file.
+/// Segments, links (or edges) and paths are each written in this order,
+/// so saving the same graph always gives the same file.\
+/// The containments, fragments, gaps and groups are always written in the order
+/// they have been read, followed by the ones added later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordOrder {
+    /// the order of the file the graph has been read from,
+    /// followed by the elements added later in ascending order
+    #[default]
+    Input,
+    /// ascending node id, links by their ends and paths by name
+    Id,
+    /// the nodes sorted so that the links go from a node to one that comes later (as far as the cycles allow),
+    /// links by the position of their ends and paths by name
+    Topological,
+}
+
+/// How the paths of a graph are written in a ```GFA1``` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathLines {
+    /// the paths read from a walk (or with an haplotype set later) as ```W``` records,
+    /// the other ones as ```P``` records
+    #[default]
+    Input,
+    /// every path as a ```P``` record
+    Paths,
+    /// every path as a ```W``` record, when its haplotype is known
+    /// or its name follows the PanSN convention (```sample#haplotype#seq_id```)
+    Walks,
+}
+
+/// How a graph is saved
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SaveOptions {
+    /// compress the output with gzip, as it's always done for the files whose name ends with ```.gz```
+    pub compress: bool,
+    /// the order of the records
+    pub order: RecordOrder,
+    /// the records used for the paths of a ```GFA1``` file
+    pub path_lines: PathLines,
+}
+
+// the representation of an edge with most of its ends in forward orientation
+// (11+ -> 12+ instead of 12- -> 11-), the same whatever the order the graph gives
+fn forward_edge(edge: Edge) -> Edge {
+    let Edge(left, right) = edge;
+    let reversed = Edge(right.flip(), left.flip());
+    let reverse_ends = |Edge(left, right): Edge| left.is_reverse() as u8 + right.is_reverse() as u8;
+    match reverse_ends(edge).cmp(&reverse_ends(reversed)) {
+        std::cmp::Ordering::Less => edge,
+        std::cmp::Ordering::Greater => reversed,
+        std::cmp::Ordering::Equal => edge_key(edge),
+    }
+}
+
+// the elements in the order they have been read, followed by the ones added later
+fn by_input_order<T: Copy, K: Eq + Hash>(
+    sorted: Vec<T>,
+    input: &[T],
+    key: impl Fn(T) -> K,
+) -> Vec<T> {
+    let present: HashSet<K> = sorted.iter().map(|element| key(*element)).collect();
+    let mut seen: HashSet<K> = HashSet::new();
+    let mut ordered: Vec<T> = input
+        .iter()
+        .copied()
+        .filter(|element| present.contains(&key(*element)) && seen.insert(key(*element)))
+        .collect();
+    ordered.extend(
+        sorted
+            .into_iter()
+            .filter(|element| !seen.contains(&key(*element))),
+    );
+    ordered
+}
+
+// Kahn's algorithm reading every edge from its left end to its right one;
+// when a cycle leaves no node without incoming edges the one with the smallest id is taken.
+// The edges with an end that's not one of the nodes are ignored
+fn topological_order(nodes: &[NodeId], edges: &[Edge]) -> Vec<NodeId> {
+    let mut incoming: BTreeMap<NodeId, usize> = nodes.iter().map(|id| (*id, 0)).collect();
+    let mut outgoing: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    for Edge(left, right) in edges.iter() {
+        let known = incoming.contains_key(&left.id()) && incoming.contains_key(&right.id());
+        if known && left.id() != right.id() {
+            *incoming.entry(right.id()).or_default() += 1;
+            outgoing.entry(left.id()).or_default().push(right.id());
+        }
+    }
+    let mut ready: BTreeSet<NodeId> = incoming
+        .iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(id, _)| *id)
+        .collect();
+
+    let mut order = Vec::with_capacity(nodes.len());
+    while let Some(next) = ready
+        .iter()
+        .next()
+        .or_else(|| incoming.keys().next())
+        .copied()
+    {
+        ready.remove(&next);
+        incoming.remove(&next);
+        order.push(next);
+        for target in outgoing.get(&next).into_iter().flatten() {
+            if let Some(count) = incoming.get_mut(target) {
+                *count -= 1;
+                if *count == 0 {
+                    ready.insert(*target);
+                }
+            }
+        }
+    }
+    order
+}
+
+// the records of a graph in the order asked
+struct Records<'a> {
+    segments: Vec<NodeId>,
+    edges: Vec<Edge>,
+    paths: Vec<&'a [u8]>,
+}
+
+impl<'a> Records<'a> {
+    fn new(graph: &'a GFAGraph, order: RecordOrder) -> Self {
+        let mut segments: Vec<NodeId> = graph
+            .graph
+            .handles_iter()
+            .map(|handle| handle.id())
+            .collect();
+        segments.sort();
+        let mut edges: Vec<Edge> = graph.graph.edges_iter().map(forward_edge).collect();
+        edges.sort();
+        edges.dedup();
+        let mut paths: Vec<&[u8]> = graph.graph.path_id.keys().map(|name| &name[..]).collect();
+        paths.sort();
+
+        match order {
+            RecordOrder::Id => (),
+            RecordOrder::Input => {
+                let input = &graph.input_order;
+                segments = by_input_order(segments, &input.segments, |id| id);
+                edges = by_input_order(edges, &input.edges, edge_key);
+                let input_paths: Vec<&[u8]> = input.paths.iter().map(|name| &name[..]).collect();
+                paths = by_input_order(paths, &input_paths, |name| name);
+            }
+            RecordOrder::Topological => {
+                segments = topological_order(&segments, &edges);
+                let position: HashMap<NodeId, usize> = segments
+                    .iter()
+                    .enumerate()
+                    .map(|(i, id)| (*id, i))
+                    .collect();
+                // the ends that are not segments of the graph come last
+                let position = |id: NodeId| position.get(&id).copied().unwrap_or(usize::MAX);
+                edges.sort_by_key(|edge| {
+                    let Edge(left, right) = *edge;
+                    (position(left.id()), position(right.id()), *edge)
+                });
+            }
+        }
+        Records {
+            segments,
+            edges,
+            paths,
+        }
+    }
+}
+
+// append the tags (if any) at the end of a record, close it and write it
+fn write_record(
+    writer: &mut dyn Write,
+    mut record: String,
+    tags: Option<&OptionalFields>,
+) -> io::Result<()> {
+    if let Some(tags) = tags {
+        for field in tags.iter() {
+            record.push_str(&format!("\t{}", field));
+        }
+    }
+    record.push('\n');
+    writer.write_all(record.as_bytes())
+}
+
+fn write_gfa2_records(
+    graph: &GFAGraph,
+    writer: &mut dyn Write,
+    options: &SaveOptions,
+) -> io::Result<()> {
+    write_record(
+        writer,
+        String::from("H\tVN:Z:2.0"),
+        Some(&graph.header_tags),
+    )?;
+
+    let records = Records::new(graph, options.order);
+    for id in records.segments {
+        let sequence = graph.graph.sequence(Handle::pack(id, false));
+        let record = format!(
+            "S\t{}\t{}\t{}",
+            graph.node_name(id),
+            graph.segment_length(id),
+            sequence.as_bstr()
+        );
+        write_record(writer, record, graph.get_segment_tags(id))?;
+    }
+    for fragment in graph.fragments.iter() {
+        let record = format!("F\t{}", fragment.fields().join("\t"));
+        write_record(writer, record, Some(&fragment.tags))?;
+    }
+    for edge in records.edges {
+        let Edge(left, right) = edge;
+        let (positions, alignment) = graph.gfa2_edge_positions(edge);
+        let record = format!(
+            "E\t{}\t{}\t{}\t{}\t{}",
+            graph.edge_id(edge),
+            graph.oriented_id(left),
+            graph.oriented_id(right),
+            positions.join("\t"),
+            alignment
+        );
+        write_record(writer, record, graph.get_edge_tags(edge))?;
+    }
+    for gap in graph.gaps.iter() {
+        let record = format!("G\t{}", gap.fields().join("\t"));
+        write_record(writer, record, Some(&gap.tags))?;
+    }
+    for name in records.paths {
+        let path = &graph.graph.paths[&graph.graph.path_id[name]];
+        let record = format!(
+            "O\t{}\t{}",
+            path.name.as_bstr(),
+            path_steps(graph, &path.nodes, " ")
+        );
+        write_record(writer, record, graph.get_path_tags(&path.name))?;
+    }
+    for group in graph.groups.iter() {
+        let record = format!("U\t{}", group.fields().join("\t"));
+        write_record(writer, record, Some(&group.tags))?;
+    }
+    Ok(())
+}
+
+// the haplotype a path is written with, if it's written as a walk
+fn path_walk(graph: &GFAGraph, name: &[u8], steps: &[Handle], lines: PathLines) -> Option<Walk> {
+    match lines {
+        PathLines::Input => graph.walk(name).cloned(),
+        PathLines::Paths => None,
+        PathLines::Walks => graph.walk(name).cloned().or_else(|| {
+            // a walk named without its range covers the whole sequence
+            let mut walk = Walk::from_path_name(name)?;
+            if walk.start.is_none() {
+                let length = steps
+                    .iter()
+                    .map(|step| graph.graph.sequence(*step).len())
+                    .sum();
+                walk.start = Some(0);
+                walk.end = Some(length);
+            }
+            Some(walk)
+        }),
+    }
+}
 
-use bstr::BString;
-use std::fs::File;
-use std::io::prelude::*;
-use std::path::Path;
+fn walk_steps(graph: &GFAGraph, steps: &[Handle]) -> String {
+    if steps.is_empty() {
+        return String::from("*");
+    }
+    steps
+        .iter()
+        .map(|step| {
+            let orientation = if step.is_reverse() { "<" } else { ">" };
+            format!("{}{}", orientation, graph.node_name(step.id()))
+        })
+        .collect()
+}
+
+fn write_gfa1_records(
+    graph: &GFAGraph,
+    writer: &mut dyn Write,
+    options: &SaveOptions,
+) -> io::Result<()> {
+    let records = Records::new(graph, options.order);
+    let walks: Vec<Option<Walk>> = records
+        .paths
+        .iter()
+        .map(|name| {
+            let path = &graph.graph.paths[&graph.graph.path_id[*name]];
+            path_walk(graph, name, &path.nodes, options.path_lines)
+        })
+        .collect();
+    // the walks have been introduced by GFA 1.1
+    let version = if walks.iter().any(Option::is_some) {
+        "1.1"
+    } else {
+        "1.0"
+    };
+    write_record(
+        writer,
+        format!("H\tVN:Z:{}", version),
+        Some(&graph.header_tags),
+    )?;
+
+    for id in records.segments {
+        let record = format!(
+            "S\t{}\t{}",
+            graph.node_name(id),
+            graph.graph.sequence(Handle::pack(id, false)).as_bstr()
+        );
+        write_record(writer, record, graph.get_segment_tags(id))?;
+    }
+    for edge in records.edges {
+        let Edge(left, right) = edge;
+        let overlap = match graph.edge_overlap(edge) {
+            Some(overlap) => overlap.gfa1_overlap(),
+            None => BString::from("0M"),
+        };
+        let record = format!(
+            "L\t{}\t{}\t{}\t{}\t{}",
+            graph.node_name(left.id()),
+            orientation(left),
+            graph.node_name(right.id()),
+            orientation(right),
+            overlap
+        );
+        write_record(writer, record, graph.get_edge_tags(edge))?;
+    }
+    for containment in graph.containments.iter() {
+        let record = format!("C\t{}", containment.fields().join("\t"));
+        write_record(writer, record, Some(&containment.tags))?;
+    }
+    for (name, walk) in records.paths.into_iter().zip(walks) {
+        let path = &graph.graph.paths[&graph.graph.path_id[name]];
+        let record = match walk {
+            Some(walk) => format!(
+                "W\t{}\t{}",
+                walk.fields().join("\t"),
+                walk_steps(graph, &path.nodes)
+            ),
+            None => format!(
+                "P\t{}\t{}\t*",
+                path.name.as_bstr(),
+                path_steps(graph, &path.nodes, ",")
+            ),
+        };
+        write_record(writer, record, graph.get_path_tags(&path.name))?;
+    }
+    Ok(())
+}
+
+fn is_compressed(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some(ext) if ext.eq_ignore_ascii_case("gz")
+    )
+}
+
+// a file whose name ends with .gz is always compressed
+fn file_options(path: &Path, options: &SaveOptions) -> SaveOptions {
+    let mut options = options.clone();
+    options.compress |= is_compressed(path);
+    options
+}
+
+// write the records one at a time through a buffer, without building the whole file in memory
+fn write_gfa<W: Write>(
+    graph: &GFAGraph,
+    writer: W,
+    options: &SaveOptions,
+    records: fn(&GFAGraph, &mut dyn Write, &SaveOptions) -> io::Result<()>,
+) -> io::Result<()> {
+    use flate2::{write::GzEncoder, Compression};
+
+    let mut writer = BufWriter::new(writer);
+    if options.compress {
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        records(graph, &mut encoder, options)?;
+        encoder.finish()?.flush()
+    } else {
+        records(graph, &mut writer, options)?;
+        writer.flush()
+    }
+}
+
+// the file written when no path is given: the one the graph has been read from,
+// with the extension of the format written (keeping the .gz of a compressed one)
+// or, when it has already that extension, with .edited before it, so the input is never overwritten
+fn default_path(graph: &GFAGraph, extension: &str) -> io::Result<PathBuf> {
+    let source = graph.source.as_ref().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "No output path given and the graph has not been read from a file",
+        )
+    })?;
+    let (stem, suffix) = if is_compressed(source) {
+        (source.with_extension("").with_extension(""), ".gz")
+    } else {
+        (source.with_extension(""), "")
+    };
+    let with_extension = |stem: &Path| {
+        let mut path = OsString::from(stem);
+        path.push(format!(".{}{}", extension, suffix));
+        PathBuf::from(path)
+    };
+    let path = with_extension(&stem);
+    if &path == source {
+        let mut stem = stem.into_os_string();
+        stem.push(".edited");
+        Ok(with_extension(Path::new(&stem)))
+    } else {
+        Ok(path)
+    }
+}
+
+// write a file in a temporary one in the same directory, renamed into place only when complete,
+// so the target (that can be the file the graph has been read from) is never left half written
+fn write_file_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+    let name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a file name", path.display()),
+        )
+    })?;
+    let mut temp_name = OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp = path.with_file_name(temp_name);
+
+    let written = File::create(&temp).and_then(|mut file| {
+        // an overwritten file keeps its permissions
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        write(&mut file)?;
+        file.sync_all()
+    });
+    match written.and_then(|_| fs::rename(&temp, path)) {
+        Ok(_) => Ok(()),
+        Err(why) => {
+            let _ = fs::remove_file(&temp);
+            Err(why)
+        }
+    }
+}
+
+fn save_file(
+    graph: &GFAGraph,
+    path: Option<String>,
+    extension: &str,
+    options: &SaveOptions,
+    records: fn(&GFAGraph, &mut dyn Write, &SaveOptions) -> io::Result<()>,
+) -> Result<(), GraphOperationError> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => default_path(graph, extension)?,
+    };
+    let options = file_options(&path, options);
+    write_file_atomically(&path, |file| write_gfa(graph, file, &options, records))
+        .map_err(|why| GraphOperationError::Io(path.display().to_string(), why))
+}
 
 /// Function that save a GFA2 object in a file
-/// on a specific or default location
+/// on a specific or default location, keeping the tags of every record.\
+/// The default location is the file the graph has been read from, with the ```.gfa2``` extension
+/// (```x.edited.gfa2``` when it has been read from ```x.gfa2```, so it's never overwritten).
+/// The file is written in a temporary one, renamed into place only when complete,
+/// so a failure never corrupts the file overwritten.
+/// If the name of the file ends with ```.gz``` it's compressed with gzip
 /// # Example
 /// ```ignore
 /// use handle_gfa::fileoperation::*;
 /// save_as_gfa2_file(&graph, Some(String::from("./tests/output_files/gfa2_to_file.gfa")));
 /// ```
-pub fn save_as_gfa2_file(graph: &HashGraph, path: Option<String>) -> Result<(), std::io::Error> {
-    use handlegraph2::conversion;
-
-    let path =
-        path.unwrap_or_else(|| String::from("./tests/output_files/default_path/file_gfa2.gfa2"));
-    let path = Path::new(&path);
-    let mut file = File::create(path)?;
-    let gfa_file: GFA2<BString, ()> = conversion::to_gfa2(&graph);
-    file.write_all(format!("{}", gfa_file).as_bytes())?;
-    file.sync_all()?;
-    Ok(())
+pub fn save_as_gfa2_file(
+    graph: &GFAGraph,
+    path: Option<String>,
+) -> Result<(), GraphOperationError> {
+    save_as_gfa2_file_with_options(graph, path, &SaveOptions::default())
+}
+
+/// Function that save a GFA2 object in a file
+/// on a specific or default location, as the options ask for
+/// # Example
+/// ```ignore
+/// use handle_gfa::fileoperation::*;
+/// let options = SaveOptions { compress: true, ..SaveOptions::default() };
+/// save_as_gfa2_file_with_options(&graph, Some(String::from("./tests/output_files/file.gfa2")), &options);
+/// ```
+pub fn save_as_gfa2_file_with_options(
+    graph: &GFAGraph,
+    path: Option<String>,
+    options: &SaveOptions,
+) -> Result<(), GraphOperationError> {
+    save_file(graph, path, "gfa2", options, write_gfa2_records)
+}
+
+/// Function that write a GFA2 object on any writer (a file, the standard output...),
+/// keeping the tags of every record
+/// # Example
+/// ```ignore
+/// use handle_gfa::fileoperation::*;
+/// save_as_gfa2_to_writer(&graph, std::io::stdout().lock());
+/// ```
+pub fn save_as_gfa2_to_writer<W: Write>(
+    graph: &GFAGraph,
+    writer: W,
+) -> Result<(), GraphOperationError> {
+    save_as_gfa2_to_writer_with_options(graph, writer, &SaveOptions::default())
+}
+
+/// Function that write a GFA2 object on any writer, as the options ask for
+pub fn save_as_gfa2_to_writer_with_options<W: Write>(
+    graph: &GFAGraph,
+    writer: W,
+    options: &SaveOptions,
+) -> Result<(), GraphOperationError> {
+    write_gfa(graph, writer, options, write_gfa2_records).map_err(GraphOperationError::from)
 }
 
 /// Function that save a GFA1 object in a file
-/// on a specific or default location
+/// on a specific or default location, keeping the tags of every record.\
+/// The default location is the file the graph has been read from, with the ```.gfa``` extension
+/// (```x.edited.gfa``` when it has been read from ```x.gfa```, so it's never overwritten).
+/// The file is written in a temporary one, renamed into place only when complete,
+/// so a failure never corrupts the file overwritten.
+/// If the name of the file ends with ```.gz``` it's compressed with gzip
 /// # Example
 /// ```ignore
 /// use handle_gfa::fileoperation::*;
 /// save_as_gfa1_file(&graph, Some(String::from("./tests/output_files/gfa2_to_file.gfa")));
 /// ```
-pub fn save_as_gfa1_file(graph: &HashGraph, path: Option<String>) -> Result<(), std::io::Error> {
-    use handlegraph2::conversion;
-
-    let path =
-        path.unwrap_or_else(|| String::from("./tests/output_files/default_path/file_gfa1.gfa"));
-    let path = Path::new(&path);
-    let mut file = File::create(path)?;
-    let gfa_file: GFA<BString, ()> = conversion::to_gfa(&graph);
-    file.write_all(format!("{}", gfa_file).as_bytes())?;
-    file.sync_all()?;
-    Ok(())
+pub fn save_as_gfa1_file(
+    graph: &GFAGraph,
+    path: Option<String>,
+) -> Result<(), GraphOperationError> {
+    save_as_gfa1_file_with_options(graph, path, &SaveOptions::default())
+}
+
+/// Function that save a GFA1 object in a file
+/// on a specific or default location, as the options ask for
+/// # Example
+/// ```ignore
+/// use handle_gfa::fileoperation::*;
+/// let options = SaveOptions { compress: true, ..SaveOptions::default() };
+/// save_as_gfa1_file_with_options(&graph, Some(String::from("./tests/output_files/file.gfa")), &options);
+/// ```
+pub fn save_as_gfa1_file_with_options(
+    graph: &GFAGraph,
+    path: Option<String>,
+    options: &SaveOptions,
+) -> Result<(), GraphOperationError> {
+    save_file(graph, path, "gfa", options, write_gfa1_records)
+}
+
+/// Function that write a GFA1 object on any writer (a file, the standard output...),
+/// keeping the tags of every record
+/// # Example
+/// ```ignore
+/// use handle_gfa::fileoperation::*;
+/// save_as_gfa1_to_writer(&graph, std::io::stdout().lock());
+/// ```
+pub fn save_as_gfa1_to_writer<W: Write>(
+    graph: &GFAGraph,
+    writer: W,
+) -> Result<(), GraphOperationError> {
+    save_as_gfa1_to_writer_with_options(graph, writer, &SaveOptions::default())
+}
+
+/// Function that write a GFA1 object on any writer, as the options ask for
+pub fn save_as_gfa1_to_writer_with_options<W: Write>(
+    graph: &GFAGraph,
+    writer: W,
+    options: &SaveOptions,
+) -> Result<(), GraphOperationError> {
+    write_gfa(graph, writer, options, write_gfa1_records).map_err(GraphOperationError::from)
+}
+
+/// Function that save the entries of a journal in a file
+/// on a specific or default location (```journal.patch``` in the current directory),
+/// so they can be replayed with ```apply_patch```
+/// # Example
+/// ```ignore
+/// use handle_gfa::fileoperation::*;
+/// save_patch_file(&history.patch(), Some(String::from("./tests/output_files/journal.patch")));
+/// ```
+pub fn save_patch_file(
+    patch: &[PatchEntry],
+    path: Option<String>,
+) -> Result<(), GraphOperationError> {
+    let path = path.unwrap_or_else(|| String::from("journal.patch"));
+    write_file_atomically(Path::new(&path), |file| {
+        file.write_all(to_patch_string(patch).as_bytes())
+    })
+    .map_err(|why| GraphOperationError::Io(path, why))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bstr::BString;
+    use gfa2::{gfa1::GFA, gfa2::GFA2};
     use handlegraph2::mutablehandlegraph::*;
 
     #[test]
@@ -79,7 +666,7 @@ mod tests {
 
         // save file on a specific path
         match save_as_gfa2_file(
-            &graph,
+            &GFAGraph::from(graph),
             Some(String::from("./tests/output_files/file_gfa2.gfa2")),
         ) {
             Ok(_) => println!("Handlegraph saved correctly!"),
@@ -112,11 +699,14 @@ mod tests {
         graph.append_step(&path, h2);
         graph.append_step(&path, h3);
 
-        // save file on a default path
-        match save_as_gfa2_file(&graph, None) {
-            Ok(_) => println!("Handlegraph saved correctly!"),
-            Err(why) => println!("Error: {}", why),
-        };
+        // a graph that has not been read from a file has no default location
+        let why = save_as_gfa2_file(&GFAGraph::from(graph), None).unwrap_err();
+        match why {
+            GraphOperationError::Io(_, why) => {
+                assert_eq!(why.kind(), std::io::ErrorKind::InvalidInput)
+            }
+            why => panic!("unexpected error: {}", why),
+        }
     }
 
     #[test]
@@ -146,7 +736,7 @@ mod tests {
 
         // save file on a specific path
         match save_as_gfa1_file(
-            &graph,
+            &GFAGraph::from(graph),
             Some(String::from("./tests/output_files/file_gfa1.gfa")),
         ) {
             Ok(_) => println!("Handlegraph saved correctly!"),
@@ -179,11 +769,217 @@ mod tests {
         graph.append_step(&path, h2);
         graph.append_step(&path, h3);
 
-        // save file on a default path
-        match save_as_gfa1_file(&graph, None) {
-            Ok(_) => println!("Handlegraph saved correctly!"),
-            Err(why) => println!("Error: {}", why),
+        // a graph that has not been read from a file has no default location
+        let why = save_as_gfa1_file(&GFAGraph::from(graph), None).unwrap_err();
+        match why {
+            GraphOperationError::Io(_, why) => {
+                assert_eq!(why.kind(), std::io::ErrorKind::InvalidInput)
+            }
+            why => panic!("unexpected error: {}", why),
+        }
+    }
+
+    #[test]
+    fn can_choose_a_default_path_that_is_not_the_input() {
+        let mut graph = GFAGraph::from(handlegraph2::hashgraph::HashGraph::new());
+        let mut default = |source: &str, extension: &str| {
+            graph.source = Some(PathBuf::from(source));
+            default_path(&graph, extension).unwrap()
+        };
+        assert_eq!(default("dir/x.gfa2", "gfa"), PathBuf::from("dir/x.gfa"));
+        assert_eq!(
+            default("dir/x.gfa2", "gfa2"),
+            PathBuf::from("dir/x.edited.gfa2")
+        );
+        assert_eq!(default("x.gfa.gz", "gfa2"), PathBuf::from("x.gfa2.gz"));
+        assert_eq!(default("x.gfa.gz", "gfa"), PathBuf::from("x.edited.gfa.gz"));
+    }
+
+    #[test]
+    fn can_keep_tags_after_save() {
+        use crate::graphoperation::gfa2_to_handlegraph;
+
+        let graph = gfa2_to_handlegraph("./tests/gfa2_files/big.gfa2".to_string()).unwrap();
+        save_as_gfa2_file(
+            &graph,
+            Some(String::from("./tests/output_files/file_tags.gfa2")),
+        )
+        .unwrap();
+
+        let graph = gfa2_to_handlegraph("./tests/output_files/file_tags.gfa2".to_string()).unwrap();
+        let tags = graph.get_segment_tags(3 as u64).unwrap();
+        assert_eq!(tags[0].to_string(), "KC:i:485841");
+    }
+
+    #[test]
+    fn can_keep_segment_names_after_save() {
+        use crate::graphoperation::gfa1_to_handlegraph;
+
+        let graph = gfa1_to_handlegraph("./tests/gfa1_files/named.gfa".to_string()).unwrap();
+        save_as_gfa1_file(
+            &graph,
+            Some(String::from("./tests/output_files/file_names.gfa")),
+        )
+        .unwrap();
+
+        let graph = gfa1_to_handlegraph("./tests/output_files/file_names.gfa".to_string()).unwrap();
+        let id = graph.node_id(b"chr1_ctg5").unwrap();
+        assert_eq!(graph.node_name(id), "chr1_ctg5");
+        assert_eq!(
+            graph.get_segment_tags(id).unwrap()[0].to_string(),
+            "KC:i:12"
+        );
+        assert!(graph.node_id(b"utg000123l").is_ok());
+    }
+
+    #[test]
+    fn can_write_graph_on_writer() {
+        use crate::graphoperation::*;
+
+        let graph = gfa1_to_handlegraph("./tests/gfa1_files/named.gfa".to_string()).unwrap();
+        let mut gfa1: Vec<u8> = vec![];
+        save_as_gfa1_to_writer(&graph, &mut gfa1).unwrap();
+        let mut gfa2: Vec<u8> = vec![];
+        save_as_gfa2_to_writer(&graph, &mut gfa2).unwrap();
+
+        let graph = gfa1_to_handlegraph_from_reader(&gfa1[..]).unwrap();
+        assert!(graph.node_id(b"chr1_ctg5").is_ok());
+        let graph = gfa2_to_handlegraph_from_reader(&gfa2[..]).unwrap();
+        assert!(graph.node_id(b"chr1_ctg5").is_ok());
+    }
+
+    #[test]
+    fn can_save_and_load_compressed_files() {
+        use crate::graphoperation::*;
+
+        let graph = gfa1_to_handlegraph("./tests/gfa1_files/named.gfa".to_string()).unwrap();
+        save_as_gfa2_file(
+            &graph,
+            Some(String::from("./tests/output_files/file_gz.gfa2.gz")),
+        )
+        .unwrap();
+        let mut gfa1: Vec<u8> = vec![];
+        save_as_gfa1_to_writer_with_options(
+            &graph,
+            &mut gfa1,
+            &SaveOptions {
+                compress: true,
+                ..SaveOptions::default()
+            },
+        )
+        .unwrap();
+        assert!(gfa1.starts_with(&GZIP_MAGIC));
+
+        let graph =
+            gfa2_to_handlegraph("./tests/output_files/file_gz.gfa2.gz".to_string()).unwrap();
+        assert!(graph.node_id(b"chr1_ctg5").is_ok());
+        let graph = gfa1_to_handlegraph_from_reader(&gfa1[..]).unwrap();
+        assert!(graph.node_id(b"chr1_ctg5").is_ok());
+    }
+
+    #[test]
+    fn can_save_and_replay_a_journal() {
+        use crate::graphoperation::*;
+
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        let mut history = History::new();
+        history
+            .apply(&mut graph, EditOperation::RemoveNode { id: "13".into() })
+            .unwrap();
+        save_patch_file(
+            &history.patch(),
+            Some(String::from("./tests/output_files/journal.patch")),
+        )
+        .unwrap();
+
+        let patch = read_patch_file("./tests/output_files/journal.patch".to_string()).unwrap();
+        let mut copy = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        apply_patch(&mut copy, &patch).unwrap();
+        assert!(copy.node_id(b"13").is_err());
+    }
+
+    #[test]
+    fn can_save_next_to_the_input_file() {
+        use crate::graphoperation::*;
+
+        fs::copy(
+            "./tests/gfa1_files/named.gfa",
+            "./tests/output_files/file_source.gfa",
+        )
+        .unwrap();
+        let mut graph =
+            gfa1_to_handlegraph("./tests/output_files/file_source.gfa".to_string()).unwrap();
+        graph.remove_node(b"utg000123l").unwrap();
+        // the input file is kept, and no temporary file is left behind
+        save_as_gfa1_file(&graph, None).unwrap();
+        save_as_gfa2_file(&graph, None).unwrap();
+        let names: Vec<String> = fs::read_dir("./tests/output_files")
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert!(!names.iter().any(|name| name.starts_with(".file_source")));
+
+        let graph =
+            gfa1_to_handlegraph("./tests/output_files/file_source.gfa".to_string()).unwrap();
+        assert!(graph.node_id(b"utg000123l").is_ok());
+        let graph =
+            gfa1_to_handlegraph("./tests/output_files/file_source.edited.gfa".to_string()).unwrap();
+        assert!(graph.node_id(b"utg000123l").is_err());
+        let graph =
+            gfa2_to_handlegraph("./tests/output_files/file_source.gfa2".to_string()).unwrap();
+        assert!(graph.node_id(b"chr1_ctg5").is_ok());
+    }
+
+    #[test]
+    fn can_save_records_in_order() {
+        use crate::graphoperation::*;
+
+        let gfa1 = b"S\t2\tG\nS\t3\tA\nS\t1\tC\nL\t1\t+\t2\t+\t0M\nL\t3\t+\t1\t+\t0M\nP\tz\t3+,1+\t*\nP\ta\t1+,2+\t*\n";
+        let mut graph = gfa1_to_handlegraph_from_reader(&gfa1[..]).unwrap();
+        graph.add_node(b"0", Some(b"T")).unwrap();
+        let save = |order: RecordOrder| {
+            let mut gfa: Vec<u8> = vec![];
+            let options = SaveOptions {
+                order,
+                ..SaveOptions::default()
+            };
+            save_as_gfa1_to_writer_with_options(&graph, &mut gfa, &options).unwrap();
+            String::from_utf8(gfa).unwrap()
+        };
+        let records = |gfa: &str, record: &str| -> Vec<String> {
+            gfa.lines()
+                .filter(|line| line.starts_with(record))
+                .map(|line| line.split('\t').nth(1).unwrap().to_string())
+                .collect()
+        };
+
+        let input = save(RecordOrder::Input);
+        assert_eq!(input, save(RecordOrder::Input));
+        assert_eq!(records(&input, "S"), ["2", "3", "1", "0"]);
+        assert_eq!(records(&input, "L"), ["1", "3"]);
+        assert_eq!(records(&input, "P"), ["z", "a"]);
+
+        let id = save(RecordOrder::Id);
+        assert_eq!(records(&id, "S"), ["0", "1", "2", "3"]);
+        assert_eq!(records(&id, "L"), ["1", "3"]);
+        assert_eq!(records(&id, "P"), ["a", "z"]);
+
+        let topological = save(RecordOrder::Topological);
+        assert_eq!(records(&topological, "S"), ["0", "3", "1", "2"]);
+        assert_eq!(records(&topological, "L"), ["3", "1"]);
+
+        // a link to a segment that's not in the graph comes last
+        let node = graph.node_id(b"2").unwrap();
+        graph.graph.graph.remove(&node);
+        let mut gfa: Vec<u8> = vec![];
+        let options = SaveOptions {
+            order: RecordOrder::Topological,
+            ..SaveOptions::default()
         };
+        save_as_gfa1_to_writer_with_options(&graph, &mut gfa, &options).unwrap();
+        let topological = String::from_utf8(gfa).unwrap();
+        assert_eq!(records(&topological, "S"), ["0", "3", "1"]);
+        assert_eq!(records(&topological, "L"), ["3", "1"]);
     }
 
     #[test]
@@ -1,65 +1,323 @@
 // manipulate hashgraph
-use handlegraph2::{
-    handle::{Edge, Handle, NodeId},
-    hashgraph::*,
-    mutablehandlegraph::*,
-    pathgraph::PathHandleGraph,
-};
+use gfa2::tag::OptField;
+use handlegraph2::hashgraph::*;
 
 pub mod error;
 pub use self::error::*;
 
+pub mod gfagraph;
+pub use self::gfagraph::*;
+
+pub mod convert;
+pub use self::convert::*;
+
+pub mod edit;
+
+pub mod history;
+pub use self::history::*;
+
+pub mod lenient;
+pub use self::lenient::*;
+
+pub mod namemap;
+pub use self::namemap::*;
+
+pub mod operation;
+pub use self::operation::*;
+
+pub mod overlap;
+pub use self::overlap::*;
+
+pub mod orientedid;
+pub use self::orientedid::*;
+
+pub mod patch;
+pub use self::patch::*;
+
+pub mod record;
+pub use self::record::*;
+
+pub mod removal;
+pub use self::removal::*;
+
+pub mod script;
+pub use self::script::*;
+
+pub mod tag;
+pub use self::tag::*;
+
+pub mod transaction;
+pub use self::transaction::*;
+
+pub mod validate;
+pub use self::validate::*;
+
+pub mod version;
+pub use self::version::*;
+
+pub mod walk;
+pub use self::walk::*;
+
 /// Function that reads a ```GFA2``` files passed as input and return its
-/// corresponding ```HandleGraph```
-pub fn gfa2_to_handlegraph(path: String) -> Result<HashGraph, GraphOperationError> {
-    use gfa2::{gfa2::GFA2, parser_gfa2::GFA2Parser};
+/// corresponding ```HandleGraph``` together with the tags of every record.\
+/// A file compressed with gzip (or bgzip) is decompressed while it's read
+pub fn gfa2_to_handlegraph(path: String) -> Result<GFAGraph, GraphOperationError> {
+    match std::fs::File::open(&path) {
+        Ok(file) => {
+            let mut graph =
+                gfa2_to_handlegraph_from_reader(file).map_err(|why| why.in_file(&path))?;
+            graph.source = Some(path.into());
+            Ok(graph)
+        }
+        Err(why) => Err(GraphOperationError::Io(path, why)),
+    }
+}
+
+/// Function that reads a ```GFA1``` files passed as input and return its
+/// corresponding ```HandleGraph``` together with the tags of every record.\
+/// A file compressed with gzip (or bgzip) is decompressed while it's read
+pub fn gfa1_to_handlegraph(path: String) -> Result<GFAGraph, GraphOperationError> {
+    match std::fs::File::open(&path) {
+        Ok(file) => {
+            let mut graph =
+                gfa1_to_handlegraph_from_reader(file).map_err(|why| why.in_file(&path))?;
+            graph.source = Some(path.into());
+            Ok(graph)
+        }
+        Err(why) => Err(GraphOperationError::Io(path, why)),
+    }
+}
+
+/// The first bytes of a gzip stream (and of every block of a BGZF one)
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// read the content of a gzip (or BGZF, made of many gzip members) stream
+// or of a plain one, looking at its first bytes
+fn decompressed<'a, R: std::io::Read + 'a>(
+    reader: R,
+) -> std::io::Result<Box<dyn std::io::BufRead + 'a>> {
+    use flate2::read::MultiGzDecoder;
+    use std::io::{BufRead, BufReader};
+
+    let mut reader = BufReader::new(reader);
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+// read every line together with its number (starting from 1),
+// skipping the empty ones and the comments
+fn for_each_line<R: std::io::Read>(
+    reader: R,
+    mut read: impl FnMut(usize, &[u8]) -> Result<(), GraphOperationError>,
+) -> Result<(), GraphOperationError> {
+    use std::io::BufRead;
+
+    let reader = decompressed(reader)?;
+    for (i, line) in reader.split(b'\n').enumerate() {
+        let mut line = line?;
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        if line.is_empty() || line[0] == b'#' {
+            continue;
+        }
+        read(i + 1, &line)?;
+    }
+    Ok(())
+}
+
+// the type of the record written in a line (the first field)
+fn record_type(line: &[u8]) -> String {
+    use bstr::ByteSlice;
 
-    let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
-    let gfa2: GFA2<usize, ()> = match parser.parse_file(&path) {
-        Ok(g) => g,
-        Err(why) => return Err(GraphOperationError::FileError(why.to_string())),
+    let record_type = line.split_str("\t").next().unwrap_or_default();
+    record_type.to_str_lossy().to_string()
+}
+
+// parse every line read, skipping the empty ones, the comments
+// and the records whose type is not one of the types given (as the specification asks),
+// and report the number and the record type of the line that cannot be parsed
+fn parse_lines<R: std::io::Read>(
+    reader: R,
+    record_types: &[&str],
+    mut parse: impl FnMut(&[u8]) -> Result<(), Box<dyn std::error::Error + Send + Sync>>,
+) -> Result<(), GraphOperationError> {
+    for_each_line(reader, |line, text| {
+        if !record_types.contains(&record_type(text).as_str()) {
+            return Ok(());
+        }
+        parse(text).map_err(|why| GraphOperationError::Parse {
+            line,
+            record_type: record_type(text),
+            why,
+        })
+    })
+}
+
+/// Function that reads a ```GFA2``` from any reader (a file, the standard input...)
+/// and return its corresponding ```HandleGraph``` together with the tags of every record.\
+/// The records of an unknown type (like the ```X``` and ```Y``` extensions) are skipped.\
+/// A gzip (or bgzip) stream is recognized by its first bytes and decompressed while it's read
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let graph = gfa2_to_handlegraph_from_reader(std::io::stdin()).unwrap();
+/// ```
+pub fn gfa2_to_handlegraph_from_reader<R: std::io::Read>(
+    reader: R,
+) -> Result<GFAGraph, GraphOperationError> {
+    use bstr::BString;
+    use gfa2::{
+        gfa2::{Line, GFA2},
+        parser_gfa2::GFA2Parser,
+        tag::OptionalFields,
     };
-    let graph: HashGraph = HashGraph::from_gfa2(&gfa2);
 
-    Ok(graph)
+    let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    let mut gfa2: GFA2<BString, OptionalFields> = GFA2::new();
+    parse_lines(reader, &["H", "S", "F", "E", "G", "O", "U"], |line| {
+        match parser.parse_gfa_line(line)? {
+            Line::Header(header) => gfa2.headers.push(header),
+            Line::Segment(segment) => gfa2.segments.push(segment),
+            Line::Fragment(fragment) => gfa2.fragments.push(fragment),
+            Line::Edge(edge) => gfa2.edges.push(edge),
+            Line::Gap(gap) => gfa2.gaps.push(gap),
+            Line::GroupO(group) => gfa2.groups_o.push(group),
+            Line::GroupU(group) => gfa2.groups_u.push(group),
+        }
+        Ok(())
+    })?;
+    GFAGraph::from_gfa2(&gfa2)
 }
 
-/// Function that reads a ```GFA1``` files passed as input and return its
-/// corresponding ```HandleGraph```
-pub fn gfa1_to_handlegraph(path: String) -> Result<HashGraph, GraphOperationError> {
-    use gfa2::{gfa1::GFA, parser_gfa1::GFAParser};
-
-    let parser: GFAParser<usize, ()> = GFAParser::new();
-    let gfa: GFA<usize, ()> = match parser.parse_file(&path) {
-        Ok(g) => g,
-        Err(why) => return Err(GraphOperationError::FileError(why.to_string())),
+/// Function that reads a ```GFA1``` from any reader (a file, the standard input...)
+/// and return its corresponding ```HandleGraph``` together with the tags of every record.\
+/// The walks (```W``` records of ```GFA 1.1```) become paths named after their haplotype,
+/// the records of an unknown type are skipped.\
+/// A gzip (or bgzip) stream is recognized by its first bytes and decompressed while it's read
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let graph = gfa1_to_handlegraph_from_reader(std::io::stdin()).unwrap();
+/// ```
+pub fn gfa1_to_handlegraph_from_reader<R: std::io::Read>(
+    reader: R,
+) -> Result<GFAGraph, GraphOperationError> {
+    use bstr::BString;
+    use gfa2::{
+        gfa1::{Line, GFA},
+        parser_gfa1::GFAParser,
+        tag::OptionalFields,
     };
-    let graph: HashGraph = HashGraph::from_gfa(&gfa);
 
+    let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+    let mut gfa: GFA<BString, OptionalFields> = GFA::new();
+    let mut walks = vec![];
+    parse_lines(reader, &["H", "S", "L", "C", "P", "W"], |line| {
+        // the walks are not known by the parser
+        if line.starts_with(b"W\t") {
+            walks.push(WalkRecord::parse(line)?);
+            return Ok(());
+        }
+        match parser.parse_gfa_line(line)? {
+            Line::Header(header) => gfa.header = header,
+            Line::Segment(segment) => gfa.segments.push(segment),
+            Line::Link(link) => gfa.links.push(link),
+            Line::Containment(containment) => gfa.containments.push(containment),
+            Line::Path(path) => gfa.paths.push(path),
+        }
+        Ok(())
+    })?;
+    let mut graph = GFAGraph::from_gfa(&gfa)?;
+    for walk in walks.iter() {
+        graph.add_walk_record(walk)?;
+    }
     Ok(graph)
 }
 
-/// Function that adds a node in a graph checking if the provided ```NodeId``` already exists
+/// Function that reads a ```GFA1``` or ```GFA2``` file and return the version of the format
+/// together with its corresponding ```HandleGraph```.\
+/// The version is told by the ```VN``` tag of the header or by the record types found in the file
+/// (```E F G O U``` for ```GFA2```, ```L C P W``` for ```GFA1```);
+/// the extension (```.gfa``` or ```.gfa2```) is used only when the content does not tell it
 /// # Example
 /// ```ignore
 /// use handle_gfa::graphoperation::*;
 ///
-/// let mut graph = HashGraph::from_gfa(&gfa2);
-/// graph = graph.add_node(graph, 14 as u64, Some(b"TEST_NODE_1")).unwrap();
+/// let (version, graph) = load_graph("./tests/gfa2_files/GFA1_TEST.gfa".to_string()).unwrap();
+/// assert_eq!(version, GFAVersion::GFA1);
 /// ```
-pub fn add_node<T: Into<NodeId>>(
-    mut graph: HashGraph,
-    nodeid: T,
-    sequence: Option<&[u8]>,
-) -> Result<HashGraph, GraphOperationError> {
-    let sequence = sequence.unwrap_or(b"DEFAULT_SEQUENCE");
-    let nodeid_temp = nodeid.into();
+pub fn load_graph(path: String) -> Result<(GFAVersion, GFAGraph), GraphOperationError> {
+    match std::fs::File::open(&path) {
+        Ok(file) => load_graph_with_fallback(file, GFAVersion::from_extension(&path))
+            .map_err(|why| why.in_file(&path)),
+        Err(why) => Err(GraphOperationError::Io(path, why)),
+    }
+}
 
-    if graph.get_node(&nodeid_temp).is_some() {
-        return Err(GraphOperationError::IdAlreadyExist(nodeid_temp.to_string()));
-    } 
-    graph.create_handle(sequence, nodeid_temp);   
-    Ok(graph) 
+/// Function that reads a ```GFA1``` or ```GFA2``` from any reader (a file, the standard input...)
+/// and return the version of the format, told by its content, together with its corresponding ```HandleGraph```
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let (version, graph) = load_graph_from_reader(std::io::stdin()).unwrap();
+/// ```
+pub fn load_graph_from_reader<R: std::io::Read>(
+    reader: R,
+) -> Result<(GFAVersion, GFAGraph), GraphOperationError> {
+    load_graph_with_fallback(reader, None)
+}
+
+// detect the version reading the first lines,
+// and return a reader of the whole stream, starting again from those lines
+fn with_detected_version<'a, R: std::io::Read + 'a>(
+    reader: R,
+    fallback: Option<GFAVersion>,
+) -> Result<(GFAVersion, impl std::io::Read + 'a), GraphOperationError> {
+    use std::io::{Cursor, Read};
+
+    let mut reader = decompressed(reader)?;
+    let mut consumed = vec![];
+    let version = detect_version(&mut reader, &mut consumed)?
+        .or(fallback)
+        .ok_or(GraphOperationError::UnknownVersion)?;
+    Ok((version, Cursor::new(consumed).chain(reader)))
+}
+
+fn load_graph_with_fallback<R: std::io::Read>(
+    reader: R,
+    fallback: Option<GFAVersion>,
+) -> Result<(GFAVersion, GFAGraph), GraphOperationError> {
+    let (version, reader) = with_detected_version(reader, fallback)?;
+    let graph = match version {
+        GFAVersion::GFA1 => gfa1_to_handlegraph_from_reader(reader)?,
+        GFAVersion::GFA2 => gfa2_to_handlegraph_from_reader(reader)?,
+    };
+    Ok((version, graph))
+}
+
+/// Function that adds a node in a graph checking if the provided segment name already exists.\
+/// The name can be any string without whitespaces (```14```, ```utg000123l```, ```chr1_ctg5```...)
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// graph = graph.add_node(graph, b"14", Some(b"TEST_NODE_1")).unwrap();
+/// ```
+pub fn add_node(
+    mut graph: GFAGraph,
+    nodeid: &[u8],
+    sequence: Option<&[u8]>,
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.add_node(nodeid, sequence)?;
+    Ok(graph)
 }
 
 /// Function that adds a link between 2 existing ```Nodes``` in a graph.
@@ -67,56 +325,17 @@ pub fn add_node<T: Into<NodeId>>(
 /// ```ignore
 /// use handle_gfa::graphoperation::*;
 ///
-/// let mut graph = HashGraph::from_gfa(&gfa2);
-/// graph = graph.add_link_between_nodes(graph, b"14+", b"15+").unwrap();
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// graph = graph.add_link_between_nodes(graph, b"14+", b"15+", Some(b"5M")).unwrap();
 /// ```
 pub fn add_link_between_nodes(
-    mut graph: HashGraph,
+    mut graph: GFAGraph,
     from_node: &[u8],
     to_node: &[u8],
-) -> Result<HashGraph, GraphOperationError> {
-    use bstr::ByteSlice;
-    use gfa2::gfa2::orientation::Orientation;
-
-    let last = from_node.len() - 1;
-    let left_id = from_node[..last].to_str().unwrap();
-
-    let sgn: &str = &from_node[last..].to_str().unwrap();
-    let left_orient: Orientation = match sgn {
-        "+" => Orientation::Forward,
-        "-" => Orientation::Backward,
-        _ => {
-            return Err(GraphOperationError::OrientationNotExists(
-                from_node.to_str().unwrap().to_string(),
-            ))
-        }
-    };
-
-    let last = to_node.len() - 1;
-    let right_id = to_node[..last].to_str().unwrap();
-
-    let sgn: &str = &to_node[last..].to_str().unwrap();
-    let right_orient: Orientation = match sgn {
-        "+" => Orientation::Forward,
-        "-" => Orientation::Backward,
-        _ => {
-            return Err(GraphOperationError::OrientationNotExists(
-                to_node.to_str().unwrap().to_string(),
-            ))
-        }
-    };
-
-    let right = Handle::new(right_id.parse::<u64>().unwrap(), right_orient);
-    let left = Handle::new(left_id.parse::<u64>().unwrap(), left_orient);
-
-    if graph.create_edge(Edge(left, right)) {
-        Ok(graph)
-    } else {
-        Err(GraphOperationError::EdgeNotExist(
-            format!("{}{}", from_node.to_str().unwrap(), left_orient),
-            format!("{}{}", to_node.to_str().unwrap(), right_orient),
-        ))
-    }
+    overlap: Option<&[u8]>,
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.add_link_between_nodes(from_node, to_node, overlap)?;
+    Ok(graph)
 }
 
 /// Function that adds a path to read the node of a graph
@@ -124,14 +343,14 @@ pub fn add_link_between_nodes(
 /// ```ignore
 /// use handle_gfa::graphoperation::*;
 ///
-/// let mut graph = HashGraph::from_gfa(&gfa2);
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
 /// let ids: Vec<&[u8]> = vec![b"11+", b"13+"];
 ///
 /// match add_path(graph, Some(b"TEST_PATH_1"), ids) {
 ///     Ok(g) => {
 ///         let mut x = 0;
-///         while !g.get_path(&x).is_none() {
-///             g.print_path(&x);
+///         while !g.graph.get_path(&x).is_none() {
+///             g.graph.print_path(&x);
 ///             x += 1;
 ///         }
 ///     },
@@ -139,120 +358,42 @@ pub fn add_link_between_nodes(
 /// };
 /// ```
 pub fn add_path(
-    mut graph: HashGraph,
+    mut graph: GFAGraph,
     path_id: Option<&[u8]>,
     sequence_of_id: Vec<&[u8]>,
-) -> Result<HashGraph, GraphOperationError> {
-    use bstr::ByteSlice;
-    use gfa2::gfa2::orientation::Orientation;
-
-    let path_id = path_id.unwrap_or(b"default_path_id");
-    // check if the path it's circular
-    let last = sequence_of_id.len() - 1;
-    let is_circular: bool = sequence_of_id[0] == sequence_of_id[last];
-
-    // create the path
-    let path = graph.create_path_handle(path_id, is_circular);
-    for seq in sequence_of_id.iter() {
-        let last = seq.len() - 1;
-        let seq_id = seq[..last].to_str().unwrap();
-
-        let sgn: &str = &seq[last..].to_str().unwrap();
-        let orient: Orientation = match sgn {
-            "+" => Orientation::Forward,
-            "-" => Orientation::Backward,
-            _ => {
-                return Err(GraphOperationError::OrientationNotExists(
-                    seq.to_str().unwrap().to_string(),
-                ))
-            }
-        };
-
-        let handle = Handle::new(seq_id.parse::<u64>().unwrap(), orient);
-        graph.append_step(&path, handle);
-    }
-
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.add_path(path_id, sequence_of_id)?;
     Ok(graph)
 }
 
-/// Function that removes a node in a graph checking if the provided ```NodeId``` exists
+/// Function that removes a node in a graph checking if the provided segment name exists
 /// # Example
 /// ```ignore
 /// use handle_gfa::graphoperation::*;
 ///
-/// let mut graph = HashGraph::from_gfa(&gfa2);
-/// graph = graph.remove_node(graph, 14 as u64).unwrap();
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// graph = graph.remove_node(graph, b"14").unwrap();
 /// ```
-pub fn remove_node<T: Into<NodeId>>(
-    mut graph: HashGraph,
-    nodeid: T,
-) -> Result<HashGraph, GraphOperationError> {
-    let node = nodeid.into();
-    if graph.remove_handle(node) {
-        Ok(graph)
-    } else {
-        Err(GraphOperationError::NodesNotExist(
-            node.to_string(),
-            "".to_string(),
-        ))
-    }
+pub fn remove_node(mut graph: GFAGraph, nodeid: &[u8]) -> Result<GFAGraph, GraphOperationError> {
+    graph.remove_node(nodeid)?;
+    Ok(graph)
 }
 
-/// Function that removes a link in a graph checking if the provided ```NodeId``` exists
+/// Function that removes a link in a graph checking if the provided segment names exist
 /// # Example
 /// ```ignore
 /// use handle_gfa::graphoperation::*;
 ///
-/// let mut graph = HashGraph::from_gfa(&gfa2);
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
 /// graph = graph.remove_link(graph, b"14+", b"15+").unwrap();
 /// ```
 pub fn remove_link(
-    mut graph: HashGraph,
+    mut graph: GFAGraph,
     from_node: &[u8],
     to_node: &[u8],
-) -> Result<HashGraph, GraphOperationError> {
-    use bstr::ByteSlice;
-    use gfa2::gfa2::orientation::Orientation;
-
-    let last = from_node.len() - 1;
-    let left_id = from_node[..last].to_str().unwrap();
-
-    let sgn: &str = &from_node[last..].to_str().unwrap();
-    let left_orient: Orientation = match sgn {
-        "+" => Orientation::Forward,
-        "-" => Orientation::Backward,
-        _ => {
-            return Err(GraphOperationError::OrientationNotExists(
-                from_node.to_str().unwrap().to_string(),
-            ))
-        }
-    };
-
-    let last = to_node.len() - 1;
-    let right_id = to_node[..last].to_str().unwrap();
-
-    let sgn: &str = &to_node[last..].to_str().unwrap();
-    let right_orient: Orientation = match sgn {
-        "+" => Orientation::Forward,
-        "-" => Orientation::Backward,
-        _ => {
-            return Err(GraphOperationError::OrientationNotExists(
-                to_node.to_str().unwrap().to_string(),
-            ))
-        }
-    };
-
-    let right = Handle::new(right_id.parse::<u64>().unwrap(), right_orient);
-    let left = Handle::new(left_id.parse::<u64>().unwrap(), left_orient);
-
-    if graph.remove_edge(Edge(left, right)) {
-        Ok(graph)
-    } else {
-        Err(GraphOperationError::EdgeNotExist(
-            from_node.to_str().unwrap().to_string(),
-            to_node.to_str().unwrap().to_string(),
-        ))
-    }
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.remove_link(from_node, to_node)?;
+    Ok(graph)
 }
 
 /// Function that removes a path in a graph checking if the provided ```PathName``` exists
@@ -260,197 +401,257 @@ pub fn remove_link(
 /// ```ignore
 /// use handle_gfa::graphoperation::*;
 ///
-/// let mut graph = HashGraph::from_gfa(&gfa2);
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
 /// graph = graph.remove_path(graph, Some(&BString::from("14")).unwrap();
 /// ```
 pub fn remove_path(
-    mut graph: HashGraph,
+    mut graph: GFAGraph,
     path_name: Option<&[u8]>,
-) -> Result<HashGraph, GraphOperationError> {
-    let path_name = path_name.unwrap_or(b"default_path_id");
-    if graph.remove_path(path_name) {
-        Ok(graph)
-    } else {
-        Err(GraphOperationError::PathNotExist(
-            String::from_utf8(path_name.to_vec()).expect("Invalid UTF-8 character"),
-        ))
-    }
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.remove_path(path_name)?;
+    Ok(graph)
 }
 
-/// Function that modifiws a node in a graph checking if the provided ```NodeId``` exists
+/// Function that modifiws a node in a graph checking if the provided segment name exists
 /// # Example
 /// ```ignore
 /// use handle_gfa::graphoperation::*;
 ///
-/// let mut graph = HashGraph::from_gfa(&gfa2);
-/// graph = graph.modify_node(graph, 14 as u64, b"NEW_SEQUENCE").unwrap();
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// graph = graph.modify_node(graph, b"14", b"NEW_SEQUENCE").unwrap();
 /// ```
-pub fn modify_node<T: Into<NodeId>>(
-    mut graph: HashGraph,
-    nodeid: T,
+pub fn modify_node(
+    mut graph: GFAGraph,
+    nodeid: &[u8],
     sequence: &[u8],
-) -> Result<HashGraph, GraphOperationError> {
-    let node = nodeid.into();
-
-    if graph.modify_handle(node, sequence) {
-        Ok(graph)
-    } else {
-        Err(GraphOperationError::NodesNotExist(
-            node.to_string(),
-            "".to_string(),
-        ))
-    }
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.modify_node(nodeid, sequence)?;
+    Ok(graph)
 }
 
-/// Function that modifies a link in a graph checking if the provided ```NodeId``` exists
+/// Function that modifies a link in a graph checking if the provided segment names exist
 /// # Example
 /// ```ignore
 /// use handle_gfa::graphoperation::*;
 ///
-/// let mut graph = HashGraph::from_gfa(&gfa2);
-/// graph = graph.modify_link(graph, b"14+", b"15+", b"14+", b"17-").unwrap();
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// graph = graph.modify_link(graph, b"14+", b"15+", b"14+", b"17-", None).unwrap();
 /// ```
 pub fn modify_link(
-    mut graph: HashGraph,
+    mut graph: GFAGraph,
     from_node: &[u8],
     to_node: &[u8],
     new_from_node: Option<&[u8]>,
     new_to_node: Option<&[u8]>,
-) -> Result<HashGraph, GraphOperationError> {
+    overlap: Option<&[u8]>,
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.modify_link(from_node, to_node, new_from_node, new_to_node, overlap)?;
+    Ok(graph)
+}
+
+/// Function that modifies a path in a graph checking if the provided ```PathName``` exists
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// graph = graph.modify_path(graph, b"14", vec![b"11+", b"12-"]).unwrap();
+/// ```
+pub fn modify_path(
+    mut graph: GFAGraph,
+    path_name: &[u8],
+    sequence_of_id: Vec<&[u8]>,
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.modify_path(path_name, sequence_of_id)?;
+    Ok(graph)
+}
+
+/// Function that returns an optional field of a node checking if the provided segment name exists
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// let coverage = get_node_tag(&graph, b"14", b"KC").unwrap();
+/// ```
+pub fn get_node_tag<'a>(
+    graph: &'a GFAGraph,
+    nodeid: &[u8],
+    tag: &[u8],
+) -> Result<&'a OptField, GraphOperationError> {
     use bstr::ByteSlice;
-    use gfa2::gfa2::orientation::Orientation;
-
-    let last = from_node.len() - 1;
-    let old_left_id = from_node[..last].to_str().unwrap();
-
-    let sgn: &str = &from_node[last..].to_str().unwrap();
-    let old_left_orient: Orientation = match sgn {
-        "+" => Orientation::Forward,
-        "-" => Orientation::Backward,
-        _ => {
-            return Err(GraphOperationError::OrientationNotExists(
-                from_node.to_str().unwrap().to_string(),
-            ))
-        }
-    };
 
-    let last = to_node.len() - 1;
-    let old_right_id = to_node[..last].to_str().unwrap();
-
-    let sgn: &str = &to_node[last..].to_str().unwrap();
-    let old_right_orient: Orientation = match sgn {
-        "+" => Orientation::Forward,
-        "-" => Orientation::Backward,
-        _ => {
-            return Err(GraphOperationError::OrientationNotExists(
-                to_node.to_str().unwrap().to_string(),
-            ))
-        }
-    };
+    let node = graph.node_id(nodeid)?;
+    find_tag(graph.get_segment_tags(node), tag).ok_or_else(|| {
+        GraphOperationError::TagNotExist(
+            tag.to_str_lossy().to_string(),
+            format!("node {}", nodeid.to_str_lossy()),
+        )
+    })
+}
 
-    let old_right = Handle::new(old_right_id.parse::<u64>().unwrap(), old_right_orient);
-    let old_left = Handle::new(old_left_id.parse::<u64>().unwrap(), old_left_orient);
-
-    let new_right = match new_to_node {
-        Some(id) => {
-            let last = id.len() - 1;
-            let new_right_id = id[..last].to_str().unwrap();
-
-            let sgn: &str = &id[last..].to_str().unwrap();
-            let new_right_orient: Orientation = match sgn {
-                "+" => Orientation::Forward,
-                "-" => Orientation::Backward,
-                _ => {
-                    return Err(GraphOperationError::OrientationNotExists(
-                        id.to_str().unwrap().to_string(),
-                    ))
-                }
-            };
-            Some(Handle::new(
-                new_right_id.parse::<u64>().unwrap(),
-                new_right_orient,
-            ))
-        }
-        None => Some(old_right),
-    };
+/// Function that sets (or replaces) an optional field of a node checking if the provided segment name exists
+/// and if the type of the field follows the GFA specification
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// graph = set_node_tag(graph, b"14", b"CL:Z:red").unwrap();
+/// ```
+pub fn set_node_tag(
+    mut graph: GFAGraph,
+    nodeid: &[u8],
+    field: &[u8],
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.set_node_tag(nodeid, field)?;
+    Ok(graph)
+}
 
-    let new_left = match new_from_node {
-        Some(id) => {
-            let last = id.len() - 1;
-            let new_left_id = id[..last].to_str().unwrap();
-
-            let sgn: &str = &id[last..].to_str().unwrap();
-            let new_left_orient: Orientation = match sgn {
-                "+" => Orientation::Forward,
-                "-" => Orientation::Backward,
-                _ => {
-                    return Err(GraphOperationError::OrientationNotExists(
-                        id.to_str().unwrap().to_string(),
-                    ))
-                }
-            };
-            Some(Handle::new(
-                new_left_id.parse::<u64>().unwrap(),
-                new_left_orient,
-            ))
-        }
-        None => Some(old_left),
-    };
+/// Function that removes an optional field of a node checking if the provided segment name and field exist
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// graph = remove_node_tag(graph, b"14", b"CL").unwrap();
+/// ```
+pub fn remove_node_tag(
+    mut graph: GFAGraph,
+    nodeid: &[u8],
+    tag: &[u8],
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.remove_node_tag(nodeid, tag)?;
+    Ok(graph)
+}
 
-    if graph.modify_edge(Edge(old_left, old_right), new_left, new_right) {
-        Ok(graph)
-    } else {
-        Err(GraphOperationError::EdgeNotExist(
-            format!("{}{}", old_left.id().to_string(), old_left_orient),
-            format!("{}{}", old_right.id().to_string(), old_right_orient),
-        ))
-    }
+/// Function that returns an optional field of a link checking if the provided link exists
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// let coverage = get_link_tag(&graph, b"14+", b"15+", b"KC").unwrap();
+/// ```
+pub fn get_link_tag<'a>(
+    graph: &'a GFAGraph,
+    from_node: &[u8],
+    to_node: &[u8],
+    tag: &[u8],
+) -> Result<&'a OptField, GraphOperationError> {
+    use bstr::ByteSlice;
+
+    let edge = graph.existing_edge(from_node, to_node)?;
+    find_tag(graph.get_edge_tags(edge), tag).ok_or_else(|| {
+        GraphOperationError::TagNotExist(
+            tag.to_str_lossy().to_string(),
+            format!(
+                "link {} -> {}",
+                from_node.to_str_lossy(),
+                to_node.to_str_lossy()
+            ),
+        )
+    })
 }
 
-/// Function that modifies a path in a graph checking if the provided ```PathName``` exists
+/// Function that sets (or replaces) an optional field of a link checking if the provided link exists
+/// and if the type of the field follows the GFA specification
 /// # Example
 /// ```ignore
 /// use handle_gfa::graphoperation::*;
 ///
-/// let mut graph = HashGraph::from_gfa(&gfa2);
-/// graph = graph.modify_path(graph, b"14", vec![b"11+", b"12-"]).unwrap();
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// graph = set_link_tag(graph, b"14+", b"15+", b"KC:i:42").unwrap();
 /// ```
-pub fn modify_path(
-    mut graph: HashGraph,
+pub fn set_link_tag(
+    mut graph: GFAGraph,
+    from_node: &[u8],
+    to_node: &[u8],
+    field: &[u8],
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.set_link_tag(from_node, to_node, field)?;
+    Ok(graph)
+}
+
+/// Function that removes an optional field of a link checking if the provided link and field exist
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// graph = remove_link_tag(graph, b"14+", b"15+", b"KC").unwrap();
+/// ```
+pub fn remove_link_tag(
+    mut graph: GFAGraph,
+    from_node: &[u8],
+    to_node: &[u8],
+    tag: &[u8],
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.remove_link_tag(from_node, to_node, tag)?;
+    Ok(graph)
+}
+
+/// Function that returns an optional field of a path checking if the provided ```PathName``` exists
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// let colour = get_path_tag(&graph, b"14", b"CL").unwrap();
+/// ```
+pub fn get_path_tag<'a>(
+    graph: &'a GFAGraph,
     path_name: &[u8],
-    sequence_of_id: Vec<&[u8]>,
-) -> Result<HashGraph, GraphOperationError> {
+    tag: &[u8],
+) -> Result<&'a OptField, GraphOperationError> {
     use bstr::ByteSlice;
-    use gfa2::gfa2::orientation::Orientation;
-
-    let path_name = path_name;
-    let mut handles: Vec<Handle> = vec![];
-
-    for seq in sequence_of_id.iter() {
-        let last = seq.len() - 1;
-        let seq_id = seq[..last].to_str().unwrap();
-
-        let sgn: &str = &seq[last..].to_str().unwrap();
-        let orient: Orientation = match sgn {
-            "+" => Orientation::Forward,
-            "-" => Orientation::Backward,
-            _ => {
-                return Err(GraphOperationError::OrientationNotExists(
-                    seq.to_str().unwrap().to_string(),
-                ))
-            }
-        };
 
-        let handle = Handle::new(seq_id.parse::<u64>().unwrap(), orient);
-        handles.push(handle);
-    }
-    if graph.modify_path(path_name, handles) {
-        Ok(graph)
-    } else {
-        Err(GraphOperationError::PathNotExist(
-            String::from_utf8(path_name.to_vec()).expect("Invalid UTF-8 character"),
-        ))
+    if !graph.has_path(path_name) {
+        return Err(GraphOperationError::PathNotExist(
+            path_name.to_str_lossy().to_string(),
+        ));
     }
+    find_tag(graph.get_path_tags(path_name), tag).ok_or_else(|| {
+        GraphOperationError::TagNotExist(
+            tag.to_str_lossy().to_string(),
+            format!("path {}", path_name.to_str_lossy()),
+        )
+    })
+}
+
+/// Function that sets (or replaces) an optional field of a path checking if the provided ```PathName``` exists
+/// and if the type of the field follows the GFA specification
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// graph = set_path_tag(graph, b"14", b"CL:Z:blue").unwrap();
+/// ```
+pub fn set_path_tag(
+    mut graph: GFAGraph,
+    path_name: &[u8],
+    field: &[u8],
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.set_path_tag(path_name, field)?;
+    Ok(graph)
+}
+
+/// Function that removes an optional field of a path checking if the provided ```PathName``` and field exist
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let mut graph = GFAGraph::from_gfa2(&gfa2).unwrap();
+/// graph = remove_path_tag(graph, b"14", b"CL").unwrap();
+/// ```
+pub fn remove_path_tag(
+    mut graph: GFAGraph,
+    path_name: &[u8],
+    tag: &[u8],
+) -> Result<GFAGraph, GraphOperationError> {
+    graph.remove_path_tag(path_name, tag)?;
+    Ok(graph)
 }
 
 /// Print an HashGraph object in a simplified way
@@ -512,8 +713,8 @@ mod tests {
     fn can_convert_file_to_handlegraph() {
         match gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()) {
             Ok(g) => {
-                let graph: HashGraph = g;
-                print_simple_graph(&graph)
+                let graph: GFAGraph = g;
+                print_simple_graph(&graph.graph)
             }
             Err(why) => println!("Error: {}", why),
         };
@@ -523,10 +724,10 @@ mod tests {
     fn can_modify_node() {
         match gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()) {
             Ok(g) => {
-                let graph: HashGraph = g;
-                print_simple_graph(&graph);
-                match modify_node(graph, 11 as u64, b"NEW_TEST_SEQUENCE") {
-                    Ok(g) => print_simple_graph(&g),
+                let graph: GFAGraph = g;
+                print_simple_graph(&graph.graph);
+                match modify_node(graph, b"11", b"NEW_TEST_SEQUENCE") {
+                    Ok(g) => print_simple_graph(&g.graph),
                     Err(why) => println!("Error: {}", why),
                 };
             }
@@ -538,10 +739,10 @@ mod tests {
     fn can_modify_edge() {
         match gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()) {
             Ok(g) => {
-                let graph: HashGraph = g;
-                print_simple_graph(&graph);
-                match modify_link(graph, b"11+", b"13+", Some(b"13+"), Some(b"11+")) {
-                    Ok(g) => print_simple_graph(&g),
+                let graph: GFAGraph = g;
+                print_simple_graph(&graph.graph);
+                match modify_link(graph, b"11+", b"13+", Some(b"13+"), Some(b"11+"), None) {
+                    Ok(g) => print_simple_graph(&g.graph),
                     Err(why) => println!("Error: {}", why),
                 };
             }
@@ -553,11 +754,11 @@ mod tests {
     fn can_modify_path() {
         match gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()) {
             Ok(g) => {
-                let graph: HashGraph = g;
-                print_simple_graph(&graph);
+                let graph: GFAGraph = g;
+                print_simple_graph(&graph.graph);
                 //let smaller path = "11+ 12-";
                 match modify_path(graph, b"14", vec![b"11+", b"12-"]) {
-                    Ok(g) => print_simple_graph(&g),
+                    Ok(g) => print_simple_graph(&g.graph),
                     Err(why) => println!("Error: {}", why),
                 };
             }
@@ -569,10 +770,10 @@ mod tests {
     fn can_remove_node() {
         match gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()) {
             Ok(g) => {
-                let graph: HashGraph = g;
-                print_simple_graph(&graph);
-                match remove_node(graph, 11 as u64) {
-                    Ok(g) => print_simple_graph(&g),
+                let graph: GFAGraph = g;
+                print_simple_graph(&graph.graph);
+                match remove_node(graph, b"11") {
+                    Ok(g) => print_simple_graph(&g.graph),
                     Err(why) => println!("Error: {}", why),
                 };
             }
@@ -584,10 +785,10 @@ mod tests {
     fn can_remove_edge() {
         match gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()) {
             Ok(g) => {
-                let graph: HashGraph = g;
-                print_simple_graph(&graph);
+                let graph: GFAGraph = g;
+                print_simple_graph(&graph.graph);
                 match remove_link(graph, b"12-", b"13+") {
-                    Ok(g) => print_simple_graph(&g),
+                    Ok(g) => print_simple_graph(&g.graph),
                     Err(why) => println!("Error: {}", why),
                 };
             }
@@ -601,10 +802,10 @@ mod tests {
 
         match gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()) {
             Ok(g) => {
-                let graph: HashGraph = g;
-                print_simple_graph(&graph);
+                let graph: GFAGraph = g;
+                print_simple_graph(&graph.graph);
                 match remove_path(graph, Some(&BString::from("14"))) {
-                    Ok(g) => print_simple_graph(&g),
+                    Ok(g) => print_simple_graph(&g.graph),
                     Err(why) => println!("Error: {}", why),
                 };
             }
@@ -618,10 +819,10 @@ mod tests {
         let gfa2: GFA2<usize, ()> = parser
             .parse_file("./tests/gfa2_files/spec_q7.gfa2")
             .unwrap();
-        let graph2 = HashGraph::from_gfa2(&gfa2);
-        print_simple_graph(&graph2);
-        match add_node(graph2, 14 as u64, Some(b"TEST_NODE_1")) {
-            Ok(g) => print_simple_graph(&g),
+        let graph2 = GFAGraph::from(HashGraph::from_gfa2(&gfa2));
+        print_simple_graph(&graph2.graph);
+        match add_node(graph2, b"14", Some(b"TEST_NODE_1")) {
+            Ok(g) => print_simple_graph(&g.graph),
             Err(why) => println!("Error: {}", why),
         };
     }
@@ -632,13 +833,13 @@ mod tests {
         let gfa2: GFA2<usize, ()> = parser
             .parse_file("./tests/gfa2_files/spec_q7.gfa2")
             .unwrap();
-        let mut graph = HashGraph::from_gfa2(&gfa2);
+        let mut graph = GFAGraph::from(HashGraph::from_gfa2(&gfa2));
 
-        graph = add_node(graph, 14 as u64, Some(b"TEST_NODE_1")).unwrap();
-        graph = add_node(graph, 15 as u64, Some(b"TEST_NODE_2")).unwrap();
-        print_simple_graph(&graph);
-        match add_link_between_nodes(graph, b"14+", b"15+") {
-            Ok(g) => print_simple_graph(&g),
+        graph = add_node(graph, b"14", Some(b"TEST_NODE_1")).unwrap();
+        graph = add_node(graph, b"15", Some(b"TEST_NODE_2")).unwrap();
+        print_simple_graph(&graph.graph);
+        match add_link_between_nodes(graph, b"14+", b"15+", None) {
+            Ok(g) => print_simple_graph(&g.graph),
             Err(why) => println!("Error: {}", why),
         };
     }
@@ -649,12 +850,160 @@ mod tests {
         let gfa2: GFA2<usize, ()> = parser
             .parse_file("./tests/gfa2_files/spec_q7.gfa2")
             .unwrap();
-        let graph = HashGraph::from_gfa2(&gfa2);
+        let graph = GFAGraph::from(HashGraph::from_gfa2(&gfa2));
         let ids: Vec<&[u8]> = vec![b"11+", b"13+"];
-        print_simple_graph(&graph);
+        print_simple_graph(&graph.graph);
         match add_path(graph, Some(b"TEST_PATH_1"), ids) {
-            Ok(g) => print_simple_graph(&g),
+            Ok(g) => print_simple_graph(&g.graph),
             Err(why) => println!("Error: {}", why),
         };
     }
+
+    #[test]
+    fn can_edit_named_segments() {
+        let mut graph = gfa1_to_handlegraph("./tests/gfa1_files/named.gfa".to_string()).unwrap();
+
+        graph = add_node(graph, b"utg000124l", Some(b"GATTACA")).unwrap();
+        graph = add_link_between_nodes(graph, b"7+", b"utg000124l+", None).unwrap();
+        graph = add_path(graph, Some(b"new_path"), vec![b"7+", b"utg000124l+"]).unwrap();
+        graph = modify_node(graph, b"chr1_ctg5", b"TCAAGGA").unwrap();
+        graph = modify_link(
+            graph,
+            b"utg000123l+",
+            b"7+",
+            None,
+            Some(b"utg000124l+"),
+            None,
+        )
+        .unwrap();
+        assert!(add_node(graph.clone(), b"chr1_ctg5", None).is_err());
+        assert!(add_link_between_nodes(graph.clone(), b"7+", b"missing+", None).is_err());
+
+        graph = remove_node(graph, b"chr1_ctg5").unwrap();
+        assert!(graph.node_id(b"chr1_ctg5").is_err());
+        assert!(remove_node(graph.clone(), b"chr1_ctg5").is_err());
+        print_simple_graph(&graph.graph);
+    }
+
+    #[test]
+    fn cannot_use_wrong_oriented_ids() {
+        let graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+
+        for (from, to) in [(&b""[..], &b"13+"[..]), (b"11", b"13+"), (b"11+", b"+")].iter() {
+            assert!(add_link_between_nodes(graph.clone(), from, to, None).is_err());
+            assert!(remove_link(graph.clone(), from, to).is_err());
+            assert!(modify_link(graph.clone(), from, to, None, None, None).is_err());
+        }
+        assert!(add_path(graph.clone(), None, vec![]).is_err());
+        assert!(add_path(graph.clone(), None, vec![b"11+", b""]).is_err());
+        assert!(modify_path(graph, b"14", vec![b"11+", b"12"]).is_err());
+    }
+
+    #[test]
+    fn can_set_and_remove_tags() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+
+        graph = set_node_tag(graph, b"11", b"KC:i:42").unwrap();
+        graph = set_node_tag(graph, b"11", b"CL:Z:red").unwrap();
+        graph = set_node_tag(graph, b"11", b"KC:i:43").unwrap();
+        assert_eq!(
+            get_node_tag(&graph, b"11", b"KC").unwrap().to_string(),
+            "KC:i:43"
+        );
+
+        graph = set_link_tag(graph, b"11+", b"13+", b"ID:Z:link_1").unwrap();
+        assert!(get_link_tag(&graph, b"13-", b"11-", b"ID").is_ok());
+        graph = set_path_tag(graph, b"14", b"CL:Z:blue").unwrap();
+
+        graph = remove_node_tag(graph, b"11", b"CL").unwrap();
+        assert!(get_node_tag(&graph, b"11", b"CL").is_err());
+        graph = remove_path_tag(graph, b"14", b"CL").unwrap();
+        assert!(remove_path_tag(graph, b"14", b"CL").is_err());
+    }
+
+    #[test]
+    fn cannot_set_wrong_tags() {
+        let graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+
+        // KC must be an integer
+        match set_node_tag(graph.clone(), b"11", b"KC:Z:many") {
+            Err(GraphOperationError::TagTypeMismatch(_, _)) => (),
+            _ => panic!("KC:Z should be rejected"),
+        }
+        for field in [
+            &b"KC:i:4.2"[..],
+            b"K:i:1",
+            b"xx:H:ABC",
+            b"xx:B:c,300",
+            b"xx:q:1",
+        ]
+        .iter()
+        {
+            assert!(set_node_tag(graph.clone(), b"11", field).is_err());
+        }
+        assert!(set_node_tag(graph.clone(), b"42", b"KC:i:1").is_err());
+        assert!(set_link_tag(graph, b"11+", b"12+", b"KC:i:1").is_err());
+    }
+
+    #[test]
+    fn can_read_graph_from_reader() {
+        let gfa2 = b"H\tVN:Z:2.0\n# a comment\nS\t11\t5\tACCTT\r\nS\t12\t6\tTCAAGG\n\nE\t*\t11+\t12-\t0\t0$\t0\t0$\t0M\n";
+        let graph = gfa2_to_handlegraph_from_reader(&gfa2[..]).unwrap();
+        assert!(graph.existing_edge(b"11+", b"12-").is_ok());
+
+        let gfa1 = std::fs::File::open("./tests/gfa1_files/named.gfa").unwrap();
+        let graph = gfa1_to_handlegraph_from_reader(gfa1).unwrap();
+        assert!(graph.node_id(b"utg000123l").is_ok());
+
+        // the records of an unknown type are skipped, the malformed ones are not
+        let graph = gfa2_to_handlegraph_from_reader(&b"S\t11\t5\tACCTT\nX\twrong\n"[..]).unwrap();
+        assert!(graph.node_id(b"11").is_ok());
+        for path in [
+            "./tests/gfa2_files/sample.gfa2",
+            "./tests/gfa2_files/sample2.gfa2",
+        ]
+        .iter()
+        {
+            assert!(gfa2_to_handlegraph(path.to_string()).is_ok());
+        }
+        let why = gfa2_to_handlegraph_from_reader(&b"S\t11\t5\tACCTT\nS\twrong\n"[..])
+            .err()
+            .unwrap();
+        match &why {
+            GraphOperationError::Parse {
+                line, record_type, ..
+            } => assert_eq!((*line, record_type.as_str()), (2, "S")),
+            _ => panic!("the second line should not be parsed"),
+        }
+        // the error of the parser is kept
+        assert!(std::error::Error::source(&why).is_some());
+        match load_graph("./tests/gfa2_files/not_a_file.gfa2".to_string()) {
+            Err(why @ GraphOperationError::Io(_, _)) => {
+                assert!(why
+                    .to_string()
+                    .starts_with("./tests/gfa2_files/not_a_file.gfa2: "));
+                assert!(std::error::Error::source(&why).is_some());
+            }
+            _ => panic!("a missing file should be an I/O error"),
+        }
+    }
+
+    #[test]
+    fn can_load_graph_detecting_the_version() {
+        // a GFA1 file with the extension of a GFA2 one
+        let (version, graph) = load_graph("./tests/gfa2_files/GFA1_TEST.gfa".to_string()).unwrap();
+        assert_eq!(version, GFAVersion::GFA1);
+        assert!(graph.node_id(b"1").is_ok());
+
+        let (version, _) = load_graph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        assert_eq!(version, GFAVersion::GFA2);
+
+        // no header: the links tell the version
+        let gfa1 = b"S\t11\tACCTT\nS\t12\tTCAAGG\nL\t11\t+\t12\t-\t0M\n";
+        let (version, graph) = load_graph_from_reader(&gfa1[..]).unwrap();
+        assert_eq!(version, GFAVersion::GFA1);
+        assert!(graph.existing_edge(b"11+", b"12-").is_ok());
+
+        assert!(load_graph_from_reader(&b"S\t11\tACCTT\n"[..]).is_err());
+    }
 }
@@ -0,0 +1,199 @@
+/// convert a GFA file from a version of the format to the other one, reporting what cannot be represented
+use bstr::ByteSlice;
+use std::{fmt, fs::File, io::Read};
+
+use crate::fileoperation::*;
+
+use super::*;
+
+/// A record of the input file that cannot be represented, or not completely,
+/// in the graph saved in the target version
+#[derive(Debug, Clone, PartialEq)]
+pub struct LostRecord {
+    /// the line of the input file (starting from 1)
+    pub line: usize,
+    /// the type of the record (```C```, ```F```, ```G```...)
+    pub record_type: String,
+    /// the fields of the record that are lost, empty when the whole record is
+    pub fields: Vec<String>,
+    pub reason: String,
+}
+
+impl fmt::Display for LostRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {} ({} record)", self.line, self.record_type)?;
+        if !self.fields.is_empty() {
+            write!(f, " [{}]", self.fields.join(", "))?;
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+/// The result of a conversion: the versions of the input and output files
+/// and every record of the input that cannot be represented in the output
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionReport {
+    pub from: GFAVersion,
+    pub to: GFAVersion,
+    pub lost: Vec<LostRecord>,
+}
+
+impl ConversionReport {
+    pub fn is_lossless(&self) -> bool {
+        self.lost.is_empty()
+    }
+}
+
+// the fields (and the reason) of a record that cannot be represented in the target version, if any
+fn lost_fields(
+    fields: &[&[u8]],
+    from: GFAVersion,
+    to: GFAVersion,
+) -> Option<(Vec<String>, String)> {
+    let field = |i: usize| fields.get(i).copied().unwrap_or(b"*");
+    let whole = |reason: &str| Some((vec![], reason.to_string()));
+    match (from, fields[0]) {
+        (GFAVersion::GFA1, b"C") if to == GFAVersion::GFA2 => {
+            whole("the containments are written only in GFA1")
+        }
+        (GFAVersion::GFA1, b"W") if to == GFAVersion::GFA2 => Some((
+            vec![
+                "sample".to_string(),
+                "haplotype".to_string(),
+                "seq_id".to_string(),
+                "range".to_string(),
+            ],
+            "the walk is written as an O group named after its haplotype".to_string(),
+        )),
+        (GFAVersion::GFA1, b"P") if field(3) != b"*" => Some((
+            vec!["overlaps".to_string()],
+            "the overlaps of the path are not kept".to_string(),
+        )),
+        (GFAVersion::GFA2, b"F") if to == GFAVersion::GFA1 => whole("GFA1 has no fragments"),
+        (GFAVersion::GFA2, b"G") if to == GFAVersion::GFA1 => whole("GFA1 has no gaps"),
+        (GFAVersion::GFA2, b"U") if to == GFAVersion::GFA1 => whole("GFA1 has no unordered groups"),
+        (GFAVersion::GFA2, b"E") if to == GFAVersion::GFA1 => {
+            let mut lost = vec![];
+            if field(1) != b"*" {
+                lost.push("id".to_string());
+            }
+            // a link can only tell an overlap between the end of a segment and the start of the other one
+            let on_an_end = |beg: &[u8], end: &[u8]| beg == b"0" || end.ends_with(b"$");
+            let dovetail = on_an_end(field(4), field(5)) && on_an_end(field(6), field(7));
+            if !dovetail {
+                lost.push("positions".to_string());
+            }
+            if field(8) != b"*" && cigar_operations(field(8)).is_none() {
+                lost.push("alignment".to_string());
+            }
+            if lost.is_empty() {
+                return None;
+            }
+            Some((
+                lost,
+                "the edge is written as a link with the overlap of its positions".to_string(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Read a ```GFA``` file of the version ```from``` and list every record
+/// that cannot be represented, or not completely, when the graph is saved in the version ```to```
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let file = std::fs::File::open("./tests/gfa2_files/sample2.gfa2").unwrap();
+/// let report = conversion_report(file, GFAVersion::GFA2, GFAVersion::GFA1).unwrap();
+/// for lost in report.lost.iter() {
+///     println!("{}", lost);
+/// }
+/// ```
+pub fn conversion_report<R: Read>(
+    reader: R,
+    from: GFAVersion,
+    to: GFAVersion,
+) -> Result<ConversionReport, GraphOperationError> {
+    use std::io::BufRead;
+
+    let reader = decompressed(reader)?;
+    let mut lost = vec![];
+    for (i, line) in reader.split(b'\n').enumerate() {
+        let line = line?;
+        let line = line.trim_end_with(|c| c == '\r');
+        if line.is_empty() || line[0] == b'#' {
+            continue;
+        }
+        let fields: Vec<&[u8]> = line.split_str("\t").collect();
+        if let Some((fields_lost, reason)) = lost_fields(&fields, from, to) {
+            lost.push(LostRecord {
+                line: i + 1,
+                record_type: fields[0].to_str_lossy().to_string(),
+                fields: fields_lost,
+                reason,
+            });
+        }
+    }
+    Ok(ConversionReport { from, to, lost })
+}
+
+/// Convert a ```GFA1``` or ```GFA2``` file (its version is told by its content)
+/// into a file of the version ```to```, returning the records that cannot be represented in it
+/// # Example
+/// ```ignore
+/// use handle_gfa::{fileoperation::SaveOptions, graphoperation::*};
+///
+/// let report = convert(
+///     "./tests/gfa2_files/sample2.gfa2".to_string(),
+///     "./tests/output_files/sample2.gfa".to_string(),
+///     GFAVersion::GFA1,
+///     &SaveOptions::default(),
+/// ).unwrap();
+/// ```
+pub fn convert(
+    input: String,
+    output: String,
+    to: GFAVersion,
+    options: &SaveOptions,
+) -> Result<ConversionReport, GraphOperationError> {
+    let (from, graph) = load_graph(input.clone())?;
+    let file = File::open(&input).map_err(|why| GraphOperationError::Io(input.clone(), why))?;
+    let report = conversion_report(file, from, to).map_err(|why| why.in_file(&input))?;
+    match to {
+        GFAVersion::GFA1 => save_as_gfa1_file_with_options(&graph, Some(output), options)?,
+        GFAVersion::GFA2 => save_as_gfa2_file_with_options(&graph, Some(output), options)?,
+    };
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_report_what_a_conversion_loses() {
+        let report = convert(
+            "./tests/gfa2_files/spec_q7.gfa2".to_string(),
+            "./tests/output_files/spec_q7.gfa".to_string(),
+            GFAVersion::GFA1,
+            &SaveOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(report.from, GFAVersion::GFA2);
+        assert!(report.is_lossless());
+        let (_, graph) = load_graph("./tests/output_files/spec_q7.gfa".to_string()).unwrap();
+        assert!(graph.node_id(b"13").is_ok());
+
+        let gfa2 = b"H\tVN:Z:2.0\nS\t1\t3\tACG\nS\t2\t3\tTTT\n\
+            E\t*\t1+\t2+\t1\t2\t0\t2\t1,1\nG\tg1\t1+\t2+\t10\t*\nU\tu1\t1 2\n";
+        let report = conversion_report(&gfa2[..], GFAVersion::GFA2, GFAVersion::GFA1).unwrap();
+        let lines: Vec<usize> = report.lost.iter().map(|lost| lost.line).collect();
+        assert_eq!(lines, [4, 5, 6]);
+        assert_eq!(report.lost[0].fields, ["positions", "alignment"]);
+        assert!(report.lost[1].fields.is_empty());
+        assert!(!report.is_lossless());
+        let report = conversion_report(&gfa2[..], GFAVersion::GFA2, GFAVersion::GFA2).unwrap();
+        assert!(report.is_lossless());
+    }
+}
@@ -0,0 +1,572 @@
+/// edit a graph in place, without moving or cloning it
+use bstr::{BString, ByteSlice};
+use handlegraph2::{
+    handle::{Edge, Handle},
+    mutablehandlegraph::*,
+    pathgraph::PathHandleGraph,
+};
+
+use super::error::*;
+use super::gfagraph::*;
+use super::operation::*;
+use super::overlap::*;
+use super::tag::*;
+
+// a sequence is written as a single field of a GFA record
+fn check_sequence(sequence: &[u8]) -> Result<(), GraphOperationError> {
+    if sequence.is_empty() || sequence.iter().any(|c| !c.is_ascii_graphic()) {
+        return Err(GraphOperationError::InvalidSequence(
+            sequence.to_str_lossy().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Every operation checks its arguments before touching the graph,
+/// so if an error is returned the graph is left unchanged
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let mut graph = gfa2_to_handlegraph("./tests/big_files/test.gfa2".to_string()).unwrap();
+/// for i in 1..1001 {
+///     if let Err(why) = graph.remove_node(i.to_string().as_bytes()) {
+///         println!("Error: {}", why);
+///     }
+/// }
+/// ```
+impl GFAGraph {
+    /// Add a node checking if the provided segment name already exists.\
+    /// A name read only as the end of a link or a step of a path (without a segment) gets its segment
+    pub fn add_node(
+        &mut self,
+        nodeid: &[u8],
+        sequence: Option<&[u8]>,
+    ) -> Result<(), GraphOperationError> {
+        let sequence = sequence.unwrap_or(b"DEFAULT_SEQUENCE");
+        check_sequence(sequence)?;
+        if self.node_id(nodeid).is_ok() {
+            return Err(GraphOperationError::IdAlreadyExist(
+                nodeid.to_str_lossy().to_string(),
+            ));
+        }
+        let id = match self.names.get_id(nodeid) {
+            Some(id) => id,
+            None => self.names.insert(nodeid),
+        };
+        self.graph.create_handle(sequence, id);
+        Ok(())
+    }
+
+    /// Add a link between 2 existing nodes, rejected with ```NodesNotExist``` if one of them does not exist,
+    /// with the CIGAR of the overlap between their ends (a blunt link when not given)
+    pub fn add_link_between_nodes(
+        &mut self,
+        from_node: &[u8],
+        to_node: &[u8],
+        overlap: Option<&[u8]>,
+    ) -> Result<(), GraphOperationError> {
+        let overlap = overlap.map(Overlap::new).transpose()?;
+        self.add_link_with_overlap(from_node, to_node, overlap)
+    }
+
+    /// Add a link between 2 existing nodes like ```add_link_between_nodes```,
+    /// with the whole overlap (the positions of a ```GFA2``` edge too)
+    pub fn add_link_with_overlap(
+        &mut self,
+        from_node: &[u8],
+        to_node: &[u8],
+        overlap: Option<Overlap>,
+    ) -> Result<(), GraphOperationError> {
+        let (left, right) = match (
+            self.oriented_handle(from_node),
+            self.oriented_handle(to_node),
+        ) {
+            (Ok(left), Ok(right)) => (left, right),
+            // both the nodes are reported when neither exists
+            (
+                Err(GraphOperationError::NodesNotExist(left, _)),
+                Err(GraphOperationError::NodesNotExist(right, _)),
+            ) => return Err(GraphOperationError::NodesNotExist(left, right)),
+            (Err(why), _) | (_, Err(why)) => return Err(why),
+        };
+
+        if self.graph.create_edge(Edge(left, right)) {
+            self.set_edge_overlap(Edge(left, right), overlap);
+            Ok(())
+        } else {
+            Err(GraphOperationError::EdgeNotExist(
+                from_node.to_str_lossy().to_string(),
+                to_node.to_str_lossy().to_string(),
+            ))
+        }
+    }
+
+    /// Add an edge of a ```GFA2``` file: a link with the positions of its ends
+    /// and an id (```*``` when it has none) that must be new
+    pub fn add_edge(
+        &mut self,
+        id: &[u8],
+        from_node: &[u8],
+        to_node: &[u8],
+        overlap: Overlap,
+    ) -> Result<(), GraphOperationError> {
+        if id != b"*" && self.edge_ids.values().any(|other| other == id) {
+            return Err(GraphOperationError::IdAlreadyExist(
+                id.to_str_lossy().to_string(),
+            ));
+        }
+        self.add_link_with_overlap(from_node, to_node, Some(overlap))?;
+        if id != b"*" {
+            let edge = self.existing_edge(from_node, to_node)?;
+            self.edge_ids.insert(edge_key(edge), BString::from(id));
+        }
+        Ok(())
+    }
+
+    /// Add a path checking if the path name is new, every segment exists
+    /// and every 2 consecutive steps are joined by a link
+    pub fn add_path(
+        &mut self,
+        path_id: Option<&[u8]>,
+        sequence_of_id: Vec<&[u8]>,
+    ) -> Result<(), GraphOperationError> {
+        let path_id = path_id.unwrap_or(b"default_path_id");
+        self.add_path_with_links(path_id, sequence_of_id, PathLinks::Strict)
+    }
+
+    /// Add a path checking if the path name is new, every segment exists
+    /// and, as asked, the consecutive steps are joined by a link:
+    /// with ```PathLinks::Strict``` a missing link is an ```EdgeNotExist``` error,
+    /// with ```PathLinks::Create``` the missing links are added (blunt) before the path.\
+    /// If an error is returned the graph is left unchanged
+    /// # Example
+    /// ```ignore
+    /// use handle_gfa::graphoperation::*;
+    ///
+    /// let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+    /// graph.add_path_with_links(b"16", vec![b"12+", b"13+"], PathLinks::Create).unwrap();
+    /// ```
+    pub fn add_path_with_links(
+        &mut self,
+        path_id: &[u8],
+        sequence_of_id: Vec<&[u8]>,
+        links: PathLinks,
+    ) -> Result<(), GraphOperationError> {
+        if self.has_path(path_id) {
+            return Err(GraphOperationError::DuplicatePath(
+                path_id.to_str_lossy().to_string(),
+            ));
+        }
+        // check if the path it's circular
+        let is_circular: bool = match (sequence_of_id.first(), sequence_of_id.last()) {
+            (Some(first), Some(last)) => first == last,
+            _ => return Err(GraphOperationError::EmptyId),
+        };
+        let handles = self.oriented_handles(&sequence_of_id)?;
+        let missing = match links {
+            PathLinks::Unchecked => vec![],
+            _ => self.missing_path_links(&sequence_of_id)?,
+        };
+        if let (PathLinks::Strict, Some((from, to))) = (links, missing.first()) {
+            return Err(GraphOperationError::EdgeNotExist(
+                from.to_string(),
+                to.to_string(),
+            ));
+        }
+        for (from, to) in missing.iter() {
+            self.add_link_between_nodes(from, to, None)?;
+        }
+
+        let path = self.graph.create_path_handle(path_id, is_circular);
+        for handle in handles {
+            self.graph.append_step(&path, handle);
+        }
+        Ok(())
+    }
+
+    /// Remove a node, its tags, the tags of its links
+    /// and the fragments, gaps and group memberships of its segment
+    pub fn remove_node(&mut self, nodeid: &[u8]) -> Result<(), GraphOperationError> {
+        let node = self.node_id(nodeid)?;
+        if self.graph.remove_handle(node) {
+            self.names.remove(nodeid);
+            self.remove_segment_records(nodeid);
+            self.segment_tags.remove(&node);
+            self.segment_lengths.remove(&node);
+            self.edge_tags
+                .retain(|Edge(left, right), _| left.id() != node && right.id() != node);
+            self.edge_overlaps
+                .retain(|Edge(left, right), _| left.id() != node && right.id() != node);
+            self.edge_ids
+                .retain(|Edge(left, right), _| left.id() != node && right.id() != node);
+            Ok(())
+        } else {
+            Err(GraphOperationError::NodesNotExist(
+                nodeid.to_str_lossy().to_string(),
+                "".to_string(),
+            ))
+        }
+    }
+
+    /// Remove a link and its tags
+    pub fn remove_link(
+        &mut self,
+        from_node: &[u8],
+        to_node: &[u8],
+    ) -> Result<(), GraphOperationError> {
+        let edge = self.existing_edge(from_node, to_node)?;
+        if self.graph.remove_edge(edge) {
+            self.edge_tags.remove(&edge_key(edge));
+            self.edge_overlaps.remove(&edge_key(edge));
+            self.edge_ids.remove(&edge_key(edge));
+            Ok(())
+        } else {
+            Err(GraphOperationError::EdgeNotExist(
+                from_node.to_str_lossy().to_string(),
+                to_node.to_str_lossy().to_string(),
+            ))
+        }
+    }
+
+    /// Remove a path, its tags and its haplotype
+    pub fn remove_path(&mut self, path_name: Option<&[u8]>) -> Result<(), GraphOperationError> {
+        let path_name = path_name.unwrap_or(b"default_path_id");
+        if self.graph.remove_path(path_name) {
+            self.path_tags.remove(path_name.as_bstr());
+            self.walks.remove(path_name.as_bstr());
+            Ok(())
+        } else {
+            Err(GraphOperationError::PathNotExist(
+                path_name.to_str_lossy().to_string(),
+            ))
+        }
+    }
+
+    /// Change the sequence of an existing node
+    pub fn modify_node(
+        &mut self,
+        nodeid: &[u8],
+        sequence: &[u8],
+    ) -> Result<(), GraphOperationError> {
+        let node = self.node_id(nodeid)?;
+        check_sequence(sequence)?;
+        let length = self.segment_length(node);
+        if self.graph.modify_handle(node, sequence) {
+            // the positions of the edges read from a GFA2 file do not fit a sequence of another length
+            if self.segment_length(node) != length {
+                for (Edge(left, right), overlap) in self.edge_overlaps.iter_mut() {
+                    if left.id() == node || right.id() == node {
+                        overlap.positions = None;
+                    }
+                }
+            }
+            Ok(())
+        } else {
+            Err(GraphOperationError::NodesNotExist(
+                nodeid.to_str_lossy().to_string(),
+                "".to_string(),
+            ))
+        }
+    }
+
+    /// Move one or both ends of an existing link, the tags and the id follow the link.\
+    /// The overlap follows the link too, unless a new one is given;
+    /// the positions read from a ```GFA2``` file are dropped when an end is moved
+    pub fn modify_link(
+        &mut self,
+        from_node: &[u8],
+        to_node: &[u8],
+        new_from_node: Option<&[u8]>,
+        new_to_node: Option<&[u8]>,
+        overlap: Option<&[u8]>,
+    ) -> Result<(), GraphOperationError> {
+        let old_edge = self.existing_edge(from_node, to_node)?;
+        let Edge(old_left, old_right) = old_edge;
+
+        let new_left = match new_from_node {
+            Some(id) => self.oriented_handle(id)?,
+            None => old_left,
+        };
+        let new_right = match new_to_node {
+            Some(id) => self.oriented_handle(id)?,
+            None => old_right,
+        };
+        let new_edge = Edge(new_left, new_right);
+        let overlap = match overlap {
+            Some(overlap) => Some(Overlap::new(overlap)?),
+            None => self.edge_overlap(old_edge).map(|mut overlap| {
+                if new_edge != old_edge {
+                    overlap.positions = None;
+                }
+                overlap
+            }),
+        };
+
+        if self
+            .graph
+            .modify_edge(old_edge, Some(new_left), Some(new_right))
+        {
+            if let Some(tags) = self.edge_tags.remove(&edge_key(old_edge)) {
+                self.edge_tags.insert(edge_key(new_edge), tags);
+            }
+            if let Some(id) = self.edge_ids.remove(&edge_key(old_edge)) {
+                self.edge_ids.insert(edge_key(new_edge), id);
+            }
+            self.edge_overlaps.remove(&edge_key(old_edge));
+            self.set_edge_overlap(new_edge, overlap);
+            Ok(())
+        } else {
+            Err(GraphOperationError::EdgeNotExist(
+                from_node.to_str_lossy().to_string(),
+                to_node.to_str_lossy().to_string(),
+            ))
+        }
+    }
+
+    /// Replace the segments of an existing path
+    pub fn modify_path(
+        &mut self,
+        path_name: &[u8],
+        sequence_of_id: Vec<&[u8]>,
+    ) -> Result<(), GraphOperationError> {
+        if !self.has_path(path_name) {
+            return Err(GraphOperationError::PathNotExist(
+                path_name.to_str_lossy().to_string(),
+            ));
+        }
+        let handles = self.oriented_handles(&sequence_of_id)?;
+
+        if self.graph.modify_path(path_name, handles) {
+            Ok(())
+        } else {
+            Err(GraphOperationError::PathNotExist(
+                path_name.to_str_lossy().to_string(),
+            ))
+        }
+    }
+
+    /// Set (or replace) an optional field of an existing node
+    pub fn set_node_tag(&mut self, nodeid: &[u8], field: &[u8]) -> Result<(), GraphOperationError> {
+        let field = parse_tag(field)?;
+        let node = self.node_id(nodeid)?;
+
+        set_tag(self.segment_tags.entry(node).or_default(), field);
+        Ok(())
+    }
+
+    /// Remove an optional field of an existing node
+    pub fn remove_node_tag(
+        &mut self,
+        nodeid: &[u8],
+        tag: &[u8],
+    ) -> Result<(), GraphOperationError> {
+        let node = self.node_id(nodeid)?;
+        let removed = match self.segment_tags.get_mut(&node) {
+            Some(tags) => remove_tag(tags, tag),
+            None => false,
+        };
+        if removed {
+            if self.segment_tags[&node].is_empty() {
+                self.segment_tags.remove(&node);
+            }
+            Ok(())
+        } else {
+            Err(GraphOperationError::TagNotExist(
+                tag.to_str_lossy().to_string(),
+                format!("node {}", nodeid.to_str_lossy()),
+            ))
+        }
+    }
+
+    /// Set (or replace) an optional field of an existing link
+    pub fn set_link_tag(
+        &mut self,
+        from_node: &[u8],
+        to_node: &[u8],
+        field: &[u8],
+    ) -> Result<(), GraphOperationError> {
+        let field = parse_tag(field)?;
+        let edge = self.existing_edge(from_node, to_node)?;
+
+        set_tag(self.edge_tags.entry(edge_key(edge)).or_default(), field);
+        Ok(())
+    }
+
+    /// Remove an optional field of an existing link
+    pub fn remove_link_tag(
+        &mut self,
+        from_node: &[u8],
+        to_node: &[u8],
+        tag: &[u8],
+    ) -> Result<(), GraphOperationError> {
+        let key = edge_key(self.existing_edge(from_node, to_node)?);
+        let removed = match self.edge_tags.get_mut(&key) {
+            Some(tags) => remove_tag(tags, tag),
+            None => false,
+        };
+        if removed {
+            if self.edge_tags[&key].is_empty() {
+                self.edge_tags.remove(&key);
+            }
+            Ok(())
+        } else {
+            Err(GraphOperationError::TagNotExist(
+                tag.to_str_lossy().to_string(),
+                format!(
+                    "link {} -> {}",
+                    from_node.to_str_lossy(),
+                    to_node.to_str_lossy()
+                ),
+            ))
+        }
+    }
+
+    /// Set (or replace) an optional field of an existing path
+    pub fn set_path_tag(
+        &mut self,
+        path_name: &[u8],
+        field: &[u8],
+    ) -> Result<(), GraphOperationError> {
+        let field = parse_tag(field)?;
+        if !self.has_path(path_name) {
+            return Err(GraphOperationError::PathNotExist(
+                path_name.to_str_lossy().to_string(),
+            ));
+        }
+        set_tag(
+            self.path_tags.entry(BString::from(path_name)).or_default(),
+            field,
+        );
+        Ok(())
+    }
+
+    /// Remove an optional field of an existing path
+    pub fn remove_path_tag(
+        &mut self,
+        path_name: &[u8],
+        tag: &[u8],
+    ) -> Result<(), GraphOperationError> {
+        let removed = match self.path_tags.get_mut(path_name.as_bstr()) {
+            Some(tags) => remove_tag(tags, tag),
+            None => false,
+        };
+        if removed {
+            if self.path_tags[path_name.as_bstr()].is_empty() {
+                self.path_tags.remove(path_name.as_bstr());
+            }
+            Ok(())
+        } else {
+            Err(GraphOperationError::TagNotExist(
+                tag.to_str_lossy().to_string(),
+                format!("path {}", path_name.to_str_lossy()),
+            ))
+        }
+    }
+
+    /// Return the edge between 2 existing nodes referenced with their orientation
+    pub fn existing_edge(
+        &self,
+        from_node: &[u8],
+        to_node: &[u8],
+    ) -> Result<Edge, GraphOperationError> {
+        let edge = Edge(
+            self.oriented_handle(from_node)?,
+            self.oriented_handle(to_node)?,
+        );
+        if self.has_edge(edge) {
+            Ok(edge)
+        } else {
+            Err(GraphOperationError::EdgeNotExist(
+                from_node.to_str_lossy().to_string(),
+                to_node.to_str_lossy().to_string(),
+            ))
+        }
+    }
+
+    fn oriented_handles(&self, references: &[&[u8]]) -> Result<Vec<Handle>, GraphOperationError> {
+        references
+            .iter()
+            .map(|reference| self.oriented_handle(reference))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use crate::fileoperation::save_as_gfa2_to_writer;
+
+    // the graph as it's saved, to compare it regardless of the order of its maps
+    fn saved(graph: &GFAGraph) -> String {
+        let mut saved = vec![];
+        save_as_gfa2_to_writer(graph, &mut saved).unwrap();
+        String::from_utf8(saved).unwrap()
+    }
+
+    #[test]
+    fn can_edit_in_place() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+
+        graph.add_node(b"16", Some(b"GATTACA")).unwrap();
+        graph.add_link_between_nodes(b"13+", b"16+", None).unwrap();
+        graph
+            .add_path(Some(b"17"), vec![b"11+", b"13+", b"16+"])
+            .unwrap();
+        graph.modify_node(b"16", b"GATTACAGATTACA").unwrap();
+        graph
+            .modify_link(b"13+", b"16+", Some(b"12-"), None, None)
+            .unwrap();
+        graph
+            .modify_path(b"17", vec![b"11+", b"12-", b"16+"])
+            .unwrap();
+        graph.remove_path(Some(b"17")).unwrap();
+        graph.remove_link(b"12-", b"16+").unwrap();
+        graph.remove_node(b"16").unwrap();
+        print_simple_graph(&graph.graph);
+    }
+
+    #[test]
+    fn graph_unchanged_on_error() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        graph.set_link_tag(b"11+", b"13+", b"ID:Z:link_1").unwrap();
+        let before = saved(&graph);
+
+        assert!(graph.add_node(b"11", None).is_err());
+        assert!(graph.add_link_between_nodes(b"11+", b"16+", None).is_err());
+        assert!(graph.add_path(Some(b"14"), vec![b"11+"]).is_err());
+        assert!(graph.add_path(Some(b"16"), vec![b"11+", b"16+"]).is_err());
+        assert!(graph.remove_node(b"16").is_err());
+        assert!(graph.remove_link(b"11+", b"12+").is_err());
+        assert!(graph
+            .modify_link(b"11+", b"13+", Some(b"16+"), None, None)
+            .is_err());
+        assert!(graph.modify_path(b"14", vec![b"11+", b"16+"]).is_err());
+        assert!(graph.set_node_tag(b"11", b"KC:Z:wrong").is_err());
+        assert!(matches!(
+            graph.add_node(b"16", Some(b"GATT ACA")),
+            Err(GraphOperationError::InvalidSequence(_))
+        ));
+        assert!(matches!(
+            graph.modify_node(b"11", b""),
+            Err(GraphOperationError::InvalidSequence(_))
+        ));
+        assert!(matches!(
+            graph.add_path(Some(b"14"), vec![b"11+"]),
+            Err(GraphOperationError::DuplicatePath(_))
+        ));
+        assert_eq!(before, saved(&graph));
+    }
+
+    #[test]
+    fn can_add_a_node_named_only_by_a_link() {
+        let (_, mut graph) =
+            load_graph_from_reader(&b"H\tVN:Z:1.0\nS\t11\tACG\nL\t11\t+\t99\t+\t0M\n"[..]).unwrap();
+        assert!(graph.node_id(b"99").is_err());
+        let id = graph.names.get_id(b"99").unwrap();
+
+        graph.add_node(b"99", Some(b"TTG")).unwrap();
+        assert_eq!(graph.node_id(b"99").unwrap(), id);
+        assert!(graph.add_node(b"99", None).is_err());
+    }
+}
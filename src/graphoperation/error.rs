@@ -1,38 +1,195 @@
 /// define a custom error type for the program
-use std::{error, fmt};
+use std::{error, fmt, io};
 
 pub type GraphOperationResult<T> = Result<T, GraphOperationError>;
 
 #[derive(Debug)]
 pub enum GraphOperationError {
-    FileError(String),
+    /// a file or a stream that cannot be read or written, with the path (empty when not known)
+    Io(String, io::Error),
+    /// a line of a ```GFA``` file that cannot be parsed, with its number (from 1), its record type
+    /// and the error of the parser
+    Parse {
+        line: usize,
+        record_type: String,
+        why: Box<dyn error::Error + Send + Sync>,
+    },
+    UnknownVersion,
     IdAlreadyExist(String),
+    DuplicatePath(String),
     NodesNotExist(String, String),
     EdgeNotExist(String, String),
     PathNotExist(String),
-    OrientationNotExists(String),
-    Unknown,
+    /// a node that cannot be removed, with the paths that walk through it
+    NodeInPaths(String, Vec<String>),
+    RecordNotExist(String),
+    /// a record that matches more than one record of the graph
+    AmbiguousRecord(String),
+    InvalidOrientedId(String),
+    EmptyId,
+    InvalidId(String),
+    InvalidSequence(String),
+    InvalidWalk(String),
+    InvalidTag(String),
+    InvalidOverlap(String),
+    TagTypeMismatch(String, String),
+    TagNotExist(String, String),
+    OperationFailed(usize, String, Box<GraphOperationError>),
+    InvalidOperation(String),
+    InvalidPatch(usize, String),
+    PatchConflict(usize, String, String),
+    LineFailed(usize, Box<GraphOperationError>),
+    /// an error that stopped a group of operations, whose rollback failed too (with its errors):
+    /// the graph has not been restored as it was
+    RollbackFailed(Box<GraphOperationError>, Vec<GraphOperationError>),
+    InvalidVersion(String),
+    NotSupported(String),
+    /// the number of problems found validating the graph
+    InvalidGraph(usize),
+}
+
+impl GraphOperationError {
+    /// Tell the file an I/O error without a path comes from
+    pub fn in_file(self, path: &str) -> Self {
+        match self {
+            GraphOperationError::Io(file, why) if file.is_empty() => {
+                GraphOperationError::Io(path.to_string(), why)
+            }
+            why => why,
+        }
+    }
 }
 
 impl fmt::Display for GraphOperationError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use GraphOperationError as GE;
         match self {
-            GE::FileError(file) => write!(f, "{}", file),
+            GE::Io(file, why) if file.is_empty() => write!(f, "{}", why),
+            GE::Io(file, why) => write!(f, "{}: {}", file, why),
+            GE::Parse {
+                line,
+                record_type,
+                why,
+            } => write!(
+                f,
+                "Cannot parse line {} ({} record): {}",
+                line, record_type, why
+            ),
+            GE::UnknownVersion => write!(
+                f,
+                "Cannot tell the GFA version from the header, the record types or the extension"
+            ),
             GE::IdAlreadyExist(id) => write!(f, "The Id provided ({}) already exists", id),
+            GE::DuplicatePath(path) => write!(f, "The Path ({}) already exists", path),
             GE::NodesNotExist(node_left, node_right) => {
                 write!(f, "Cannot find the node(s): {} {}", node_left, node_right)
             }
             GE::EdgeNotExist(l, r) => write!(f, "The Edge ({} -> {}) did not exist", l, r),
             GE::PathNotExist(path) => write!(f, "The Path ({}) did not exist", path),
-            GE::OrientationNotExists(orientation) => write!(
+            GE::NodeInPaths(node, paths) => write!(
+                f,
+                "The node ({}) is used by the path(s): {}",
+                node,
+                paths.join(" ")
+            ),
+            GE::RecordNotExist(record) => write!(f, "The record ({}) did not exist", record),
+            GE::AmbiguousRecord(record) => write!(
+                f,
+                "The record ({}) matches more than one record, its positions are needed",
+                record
+            ),
+            GE::InvalidOrientedId(reference) => write!(
                 f,
                 "Segment reference Id ({}) did not include orientation",
-                orientation
+                reference
+            ),
+            GE::EmptyId => write!(f, "The segment reference Id cannot be empty"),
+            GE::InvalidId(id) => write!(
+                f,
+                "The segment reference Id ({}) is not a valid segment name",
+                id
+            ),
+            GE::InvalidSequence(sequence) => write!(
+                f,
+                "The sequence ({}) cannot be empty or contain whitespaces",
+                sequence
+            ),
+            GE::InvalidWalk(why) => write!(f, "The walk is not valid: {}", why),
+            GE::InvalidTag(tag) => write!(
+                f,
+                "The optional field ({}) does not follow the TAG:TYPE:VALUE format",
+                tag
             ),
-            GE::Unknown => write!(f, "Unknown error while operating on the graph"),
+            GE::InvalidOverlap(overlap) => {
+                write!(f, "The overlap ({}) is not a CIGAR string", overlap)
+            }
+            GE::TagTypeMismatch(tag, tag_type) => write!(
+                f,
+                "The optional field ({}) must be of type {}",
+                tag, tag_type
+            ),
+            GE::TagNotExist(tag, element) => {
+                write!(
+                    f,
+                    "The optional field ({}) of {} did not exist",
+                    tag, element
+                )
+            }
+            GE::OperationFailed(position, operation, why) => write!(
+                f,
+                "Operation number {} ({}) failed: {}",
+                position, operation, why
+            ),
+            GE::InvalidOperation(operation) => {
+                write!(
+                    f,
+                    "The operation ({}) is not a valid edit operation",
+                    operation
+                )
+            }
+            GE::InvalidPatch(line, why) => write!(f, "Line {} of the patch: {}", line, why),
+            GE::PatchConflict(position, operation, why) => write!(
+                f,
+                "Patch entry number {} ({}) does not match the graph: {}",
+                position, operation, why
+            ),
+            GE::LineFailed(line, why) => write!(f, "Line {}: {}", line, why),
+            GE::RollbackFailed(why, errors) => {
+                let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+                write!(
+                    f,
+                    "{}, and the graph cannot be restored: {}",
+                    why,
+                    errors.join("; ")
+                )
+            }
+            GE::InvalidVersion(version) => {
+                write!(f, "The version ({}) is not a GFA version", version)
+            }
+            GE::NotSupported(what) => write!(f, "{} is not supported", what),
+            GE::InvalidGraph(problems) => {
+                write!(f, "The graph is not coherent: {} problems found", problems)
+            }
+        }
+    }
+}
+
+impl error::Error for GraphOperationError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        use GraphOperationError as GE;
+        match self {
+            GE::Io(_, why) => Some(why),
+            GE::Parse { why, .. } => Some(why.as_ref()),
+            GE::OperationFailed(_, _, why)
+            | GE::LineFailed(_, why)
+            | GE::RollbackFailed(why, _) => Some(why.as_ref()),
+            _ => None,
         }
     }
 }
 
-impl error::Error for GraphOperationError {}
+impl From<io::Error> for GraphOperationError {
+    fn from(why: io::Error) -> Self {
+        GraphOperationError::Io(String::new(), why)
+    }
+}
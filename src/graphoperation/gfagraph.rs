@@ -0,0 +1,436 @@
+/// define the in-memory model used by all the graph operations
+use bstr::{BString, ByteSlice};
+use gfa2::{
+    gfa1::GFA,
+    gfa2::{orientation::Orientation, GFA2},
+    tag::OptionalFields,
+};
+use handlegraph2::{
+    handle::{Direction, Edge, Handle, NodeId},
+    handlegraph::HandleGraph,
+    hashgraph::HashGraph,
+    mutablehandlegraph::*,
+    pathgraph::PathHandleGraph,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    path::PathBuf,
+};
+
+use super::error::*;
+use super::namemap::NameMap;
+use super::orientedid::OrientedId;
+use super::overlap::*;
+use super::record::*;
+use super::walk::*;
+
+/// The order of the records of the file a graph has been read from.\
+/// The elements removed later are skipped when it's used, so it's never updated by the edits
+#[derive(Debug, Clone, Default)]
+pub struct InputOrder {
+    pub segments: Vec<NodeId>,
+    pub edges: Vec<Edge>,
+    pub paths: Vec<BString>,
+    /// The index of the records kept in a list, by the ends of the containments,
+    /// the ends and the positions of the fragments, the id (or the ends) of the gaps
+    /// and the id of the groups, used to add them back at their place
+    pub containments: HashMap<(BString, BString), usize>,
+    pub fragments: HashMap<(BString, BString, [BString; 4]), usize>,
+    pub gaps: HashMap<GapReference, usize>,
+    pub groups: HashMap<BString, usize>,
+}
+
+/// An ```HashGraph``` together with the optional fields (tags) and the segment names of the
+/// GFA records it has been created from.\
+/// The ```HashGraph``` stores only sequences, edges and paths identified by numeric ids,
+/// so every tag and every name is kept here and written back when the graph is saved
+#[derive(Debug, Clone)]
+pub struct GFAGraph {
+    pub graph: HashGraph,
+    pub header_tags: OptionalFields,
+    pub segment_tags: HashMap<NodeId, OptionalFields>,
+    pub edge_tags: HashMap<Edge, OptionalFields>,
+    /// The overlaps of the links that are not blunt, stored like their tags
+    pub edge_overlaps: HashMap<Edge, Overlap>,
+    /// The ids of the edges read from a ```GFA2``` file (not the ```*``` ones), stored like their tags
+    pub edge_ids: HashMap<Edge, BString>,
+    pub path_tags: HashMap<BString, OptionalFields>,
+    pub names: NameMap,
+    /// The length of the segments read without sequence (```*```) from a ```GFA2``` file
+    pub segment_lengths: HashMap<NodeId, usize>,
+    /// The haplotype of the paths read from the walks of a ```GFA 1.1``` file
+    pub walks: HashMap<BString, Walk>,
+    /// The containments of a ```GFA1``` file, kept as they have been read
+    pub containments: Vec<Containment>,
+    /// The fragments, gaps and unordered groups of a ```GFA2``` file, kept as they have been read
+    pub fragments: Vec<Fragment>,
+    pub gaps: Vec<Gap>,
+    pub groups: Vec<UnorderedGroup>,
+    /// The file the graph has been read from, used to name the output when no path is given
+    pub source: Option<PathBuf>,
+    /// The order of the records of the file the graph has been read from
+    pub input_order: InputOrder,
+}
+
+impl Default for GFAGraph {
+    fn default() -> Self {
+        GFAGraph::new()
+    }
+}
+
+impl From<HashGraph> for GFAGraph {
+    fn from(graph: HashGraph) -> Self {
+        // every node keeps its id as name
+        let mut names = NameMap::new();
+        for id in graph.graph.keys() {
+            names.insert(id.to_string().as_bytes());
+        }
+        GFAGraph {
+            graph,
+            names,
+            ..GFAGraph::new()
+        }
+    }
+}
+
+/// Check if a step of an ordered group (```11_to_13+```) is an edge, given the ids of the edges.\
+/// The edge joins the segments before and after it, so it's not a step of the path
+pub fn refers_to_edge(step: &[u8], edge_ids: &HashSet<BString>) -> bool {
+    match step.split_last() {
+        Some((_, name)) => edge_ids.contains(name.as_bstr()),
+        None => false,
+    }
+}
+
+/// Return the key used to store the tags of an ```Edge```.\
+/// The same edge can be read in both directions (```11+ -> 12-``` is ```12+ -> 11-```)
+/// so only one of the 2 representations is used
+pub fn edge_key(edge: Edge) -> Edge {
+    let Edge(left, right) = edge;
+    let reversed = Edge(right.flip(), left.flip());
+    if reversed < edge {
+        reversed
+    } else {
+        edge
+    }
+}
+
+impl GFAGraph {
+    pub fn new() -> Self {
+        GFAGraph {
+            graph: HashGraph::new(),
+            header_tags: OptionalFields::new(),
+            segment_tags: HashMap::new(),
+            edge_tags: HashMap::new(),
+            edge_overlaps: HashMap::new(),
+            edge_ids: HashMap::new(),
+            path_tags: HashMap::new(),
+            names: NameMap::new(),
+            segment_lengths: HashMap::new(),
+            walks: HashMap::new(),
+            containments: vec![],
+            fragments: vec![],
+            gaps: vec![],
+            groups: vec![],
+            source: None,
+            input_order: InputOrder::default(),
+        }
+    }
+
+    /// Create the graph associated to a ```GFA1``` object keeping all its tags
+    pub fn from_gfa(gfa: &GFA<BString, OptionalFields>) -> Result<Self, GraphOperationError> {
+        let mut graph = GFAGraph::new();
+        graph.add_header_tags(&gfa.header.optional);
+
+        for segment in gfa.segments.iter() {
+            let id = graph.names.insert(&segment.name);
+            graph.graph.create_handle(&segment.sequence, id);
+            graph.input_order.segments.push(id);
+            graph.insert_segment_tags(id, &segment.optional);
+        }
+        for link in gfa.links.iter() {
+            let left = Handle::new(graph.names.insert(&link.from_segment), link.from_orient);
+            let right = Handle::new(graph.names.insert(&link.to_segment), link.to_orient);
+            graph.insert_edge(Edge(left, right), &link.optional);
+            let overlap = Overlap {
+                alignment: link.overlap.clone(),
+                positions: None,
+            };
+            graph.set_edge_overlap(Edge(left, right), Some(overlap));
+        }
+        for path in gfa.paths.iter() {
+            let steps = path
+                .segment_names
+                .split_str(",")
+                .filter(|step| !step.is_empty())
+                .map(|step| graph.load_handle(step))
+                .collect::<Result<Vec<Handle>, GraphOperationError>>()?;
+            graph.insert_path(&path.path_name, steps, &path.optional);
+        }
+        for containment in gfa.containments.iter() {
+            let container = Handle::new(
+                graph.names.insert(&containment.container_name),
+                containment.container_orient,
+            );
+            let contained = Handle::new(
+                graph.names.insert(&containment.contained_name),
+                containment.contained_orient,
+            );
+            let container = BString::from(graph.oriented_id(container).to_string());
+            let contained = BString::from(graph.oriented_id(contained).to_string());
+            graph
+                .input_order
+                .containments
+                .entry((container.clone(), contained.clone()))
+                .or_insert(graph.containments.len());
+            graph.containments.push(Containment {
+                container,
+                contained,
+                pos: containment.pos,
+                overlap: containment.overlap.clone(),
+                tags: containment.optional.clone(),
+            });
+        }
+
+        Ok(graph)
+    }
+
+    /// Add the path read from a walk of a ```GFA 1.1``` file, named after its haplotype
+    /// (```sample#haplotype#seq_id```), keeping the haplotype to write it back as a walk
+    pub fn add_walk_record(&mut self, record: &WalkRecord) -> Result<(), GraphOperationError> {
+        let name = record.walk.path_name();
+        if self.has_path(&name) {
+            return Err(GraphOperationError::DuplicatePath(name.to_string()));
+        }
+        let steps = record
+            .steps
+            .iter()
+            .map(|step| self.load_handle(step))
+            .collect::<Result<Vec<Handle>, GraphOperationError>>()?;
+        self.insert_path(&name, steps, &record.tags);
+        self.walks.insert(name, record.walk.clone());
+        Ok(())
+    }
+
+    /// Create the graph associated to a ```GFA2``` object keeping all its tags
+    pub fn from_gfa2(gfa2: &GFA2<BString, OptionalFields>) -> Result<Self, GraphOperationError> {
+        let mut graph = GFAGraph::new();
+        for header in gfa2.headers.iter() {
+            graph.add_header_tags(&header.tag);
+        }
+
+        for segment in gfa2.segments.iter() {
+            let id = graph.names.insert(&segment.id);
+            graph.graph.create_handle(&segment.sequence, id);
+            graph.input_order.segments.push(id);
+            graph.insert_segment_tags(id, &segment.tag);
+            if segment.sequence == "*" {
+                if let Ok(length) = segment.len.to_str().unwrap_or_default().parse() {
+                    graph.segment_lengths.insert(id, length);
+                }
+            }
+        }
+        for edge in gfa2.edges.iter() {
+            let left = graph.load_handle(&edge.sid1)?;
+            let right = graph.load_handle(&edge.sid2)?;
+            graph.insert_edge(Edge(left, right), &edge.tag);
+            let overlap = Overlap {
+                alignment: edge.alignment.clone(),
+                positions: Some([
+                    edge.beg1.clone(),
+                    edge.end1.clone(),
+                    edge.beg2.clone(),
+                    edge.end2.clone(),
+                ]),
+            };
+            graph.set_edge_overlap(Edge(left, right), Some(overlap));
+            if edge.id != "*" {
+                graph
+                    .edge_ids
+                    .insert(edge_key(Edge(left, right)), edge.id.clone());
+            }
+        }
+        let edge_ids: HashSet<BString> = graph.edge_ids.values().cloned().collect();
+        for group in gfa2.groups_o.iter() {
+            let steps = group
+                .var_field
+                .split_str(" ")
+                .filter(|step| !step.is_empty() && !refers_to_edge(step, &edge_ids))
+                .map(|step| graph.load_handle(step))
+                .collect::<Result<Vec<Handle>, GraphOperationError>>()?;
+            graph.insert_path(&group.id, steps, &group.tag);
+        }
+        for fragment in gfa2.fragments.iter() {
+            let fragment = Fragment {
+                segment: fragment.id.clone(),
+                external: fragment.ext_ref.clone(),
+                sbeg: fragment.sbeg.clone(),
+                send: fragment.send.clone(),
+                fbeg: fragment.fbeg.clone(),
+                fend: fragment.fend.clone(),
+                alignment: fragment.alignment.clone(),
+                tags: fragment.tag.clone(),
+            };
+            graph
+                .input_order
+                .fragments
+                .entry(fragment.key())
+                .or_insert(graph.fragments.len());
+            graph.fragments.push(fragment);
+        }
+        for gap in gfa2.gaps.iter() {
+            let gap = Gap {
+                id: gap.id.clone(),
+                from: gap.sid1.clone(),
+                to: gap.sid2.clone(),
+                distance: gap.dist.clone(),
+                variance: gap.var.clone(),
+                tags: gap.tag.clone(),
+            };
+            graph
+                .input_order
+                .gaps
+                .entry(gap.reference())
+                .or_insert(graph.gaps.len());
+            graph.gaps.push(gap);
+        }
+        for group in gfa2.groups_u.iter() {
+            graph
+                .input_order
+                .groups
+                .entry(group.id.clone())
+                .or_insert(graph.groups.len());
+            graph.groups.push(UnorderedGroup {
+                id: group.id.clone(),
+                members: group
+                    .var_field
+                    .split_str(" ")
+                    .filter(|member| !member.is_empty())
+                    .map(BString::from)
+                    .collect(),
+                tags: group.tag.clone(),
+            });
+        }
+
+        Ok(graph)
+    }
+
+    // the records can refer to segments defined later in the file, so every name is accepted
+    fn load_handle(&mut self, reference: &[u8]) -> Result<Handle, GraphOperationError> {
+        let id = OrientedId::try_from(reference)?;
+        Ok(Handle::new(self.names.insert(&id.name), id.orientation))
+    }
+
+    // the version is always written by the save functions
+    fn add_header_tags(&mut self, tags: &OptionalFields) {
+        self.header_tags
+            .extend(tags.iter().filter(|field| &field.tag != b"VN").cloned());
+    }
+
+    fn insert_segment_tags(&mut self, id: NodeId, tags: &OptionalFields) {
+        if !tags.is_empty() {
+            self.segment_tags.insert(id, tags.clone());
+        }
+    }
+
+    fn insert_edge(&mut self, edge: Edge, tags: &OptionalFields) {
+        self.input_order.edges.push(edge);
+        if self.graph.create_edge(edge) && !tags.is_empty() {
+            self.edge_tags.insert(edge_key(edge), tags.clone());
+        }
+    }
+
+    fn insert_path(&mut self, name: &[u8], steps: Vec<Handle>, tags: &OptionalFields) {
+        self.input_order.paths.push(BString::from(name));
+        let path = self.graph.create_path_handle(name, false);
+        for step in steps {
+            self.graph.append_step(&path, step);
+        }
+        if !tags.is_empty() {
+            self.path_tags.insert(BString::from(name), tags.clone());
+        }
+    }
+
+    /// Return the ```NodeId``` of an existing segment
+    pub fn node_id(&self, name: &[u8]) -> Result<NodeId, GraphOperationError> {
+        match self.names.get_id(name) {
+            Some(id) if self.graph.get_node(&id).is_some() => Ok(id),
+            _ => Err(GraphOperationError::NodesNotExist(
+                name.to_str_lossy().to_string(),
+                "".to_string(),
+            )),
+        }
+    }
+
+    /// Return the ```Handle``` of an existing segment referenced with its orientation
+    pub fn handle(&self, id: &OrientedId) -> Result<Handle, GraphOperationError> {
+        Ok(Handle::new(self.node_id(&id.name)?, id.orientation))
+    }
+
+    /// Parse a reference to a segment with its orientation (```utg1+```)
+    /// and return the ```Handle``` of the existing segment
+    pub fn oriented_handle(&self, reference: &[u8]) -> Result<Handle, GraphOperationError> {
+        self.handle(&OrientedId::try_from(reference)?)
+    }
+
+    /// Return the name of the segment associated to a ```NodeId```
+    pub fn node_name(&self, id: NodeId) -> BString {
+        match self.names.get_name(id) {
+            Some(name) => name.clone(),
+            None => BString::from(id.to_string()),
+        }
+    }
+
+    /// Return the reference to the segment associated to an ```Handle``` (```utg1+```)
+    pub fn oriented_id(&self, handle: Handle) -> OrientedId {
+        let orientation = if handle.is_reverse() {
+            Orientation::Backward
+        } else {
+            Orientation::Forward
+        };
+        OrientedId {
+            name: self.node_name(handle.id()),
+            orientation,
+        }
+    }
+
+    /// Return the tags of a node, if any
+    pub fn get_segment_tags<T: Into<NodeId>>(&self, nodeid: T) -> Option<&OptionalFields> {
+        self.segment_tags.get(&nodeid.into())
+    }
+
+    /// Return the tags of an edge, if any
+    pub fn get_edge_tags(&self, edge: Edge) -> Option<&OptionalFields> {
+        self.edge_tags.get(&edge_key(edge))
+    }
+
+    /// Return the id of an ```Edge```, ```*``` when it has none
+    pub fn edge_id(&self, edge: Edge) -> BString {
+        match self.edge_ids.get(&edge_key(edge)) {
+            Some(id) => id.clone(),
+            None => BString::from("*"),
+        }
+    }
+
+    /// Return the tags of a path, if any
+    pub fn get_path_tags(&self, path_name: &[u8]) -> Option<&OptionalFields> {
+        self.path_tags.get(path_name.as_bstr())
+    }
+
+    /// Check if the graph contains an edge between 2 existing nodes
+    pub fn has_edge(&self, edge: Edge) -> bool {
+        let Edge(left, right) = edge;
+        self.graph.get_node(&left.id()).is_some()
+            && self.graph.get_node(&right.id()).is_some()
+            && self
+                .graph
+                .handle_edges_iter(left, Direction::Right)
+                .any(|handle| handle == right)
+    }
+
+    /// Check if the graph contains a path with the provided name
+    pub fn has_path(&self, path_name: &[u8]) -> bool {
+        self.graph.path_id.contains_key(path_name)
+    }
+}
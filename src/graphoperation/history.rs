@@ -0,0 +1,175 @@
+/// record the edits applied to a graph so they can be undone and redone
+use super::error::*;
+use super::gfagraph::*;
+use super::operation::*;
+use super::transaction::*;
+
+/// The list of the edits applied to a ```GFAGraph```.\
+/// Every entry keeps the operations that undo it, so removing a node records
+/// its sequence, its tags, the links that touched it and the paths that went through it
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+/// let mut history = History::new();
+/// history.apply(&mut graph, EditOperation::RemoveNode { id: "11".into() }).unwrap();
+/// history.undo(&mut graph).unwrap();
+/// history.redo(&mut graph).unwrap();
+/// for (i, entry) in history.iter().enumerate() {
+///     println!("{}: {}", i + 1, entry.operations[0]);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    done: Vec<Batch>,
+    undone: Vec<Batch>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History {
+            done: vec![],
+            undone: vec![],
+        }
+    }
+
+    /// Apply an operation and record it. The operations undone so far cannot be redone anymore
+    pub fn apply(
+        &mut self,
+        graph: &mut GFAGraph,
+        operation: EditOperation,
+    ) -> Result<(), GraphOperationError> {
+        self.apply_batch(graph, vec![operation])
+    }
+
+    /// Apply a group of operations all together and record them as a single entry
+    pub fn apply_batch(
+        &mut self,
+        graph: &mut GFAGraph,
+        operations: Vec<EditOperation>,
+    ) -> Result<(), GraphOperationError> {
+        let batch = graph.apply_batch(operations)?;
+        self.done.push(batch);
+        self.undone.clear();
+        Ok(())
+    }
+
+    /// Undo the last entry, returning it (or ```None``` if there is nothing to undo)
+    pub fn undo(&mut self, graph: &mut GFAGraph) -> Result<Option<&Batch>, GraphOperationError> {
+        let batch = match self.done.pop() {
+            Some(batch) => batch,
+            None => return Ok(None),
+        };
+        if let Err(why) = graph.apply_batch(batch.undo_operations()) {
+            self.done.push(batch);
+            return Err(why);
+        }
+        self.undone.push(batch);
+        Ok(self.undone.last())
+    }
+
+    /// Apply again the last entry undone, returning it (or ```None``` if there is nothing to redo)
+    pub fn redo(&mut self, graph: &mut GFAGraph) -> Result<Option<&Batch>, GraphOperationError> {
+        let batch = match self.undone.pop() {
+            Some(batch) => batch,
+            None => return Ok(None),
+        };
+        // the operations are applied again to record the state they now have to restore
+        match graph.apply_batch(batch.operations.clone()) {
+            Ok(redone) => {
+                self.done.push(redone);
+                Ok(self.done.last())
+            }
+            Err(why) => {
+                self.undone.push(batch);
+                Err(why)
+            }
+        }
+    }
+
+    /// Iterate over the entries applied (and not undone), from the oldest
+    pub fn iter(&self) -> impl Iterator<Item = &Batch> {
+        self.done.iter()
+    }
+
+    /// Iterate over the entries that can be redone, starting from the next one
+    pub fn undone(&self) -> impl Iterator<Item = &Batch> {
+        self.undone.iter().rev()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.done.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.done.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn can_undo_and_redo_node_removal() {
+        use handlegraph2::handlegraph::HandleGraph;
+
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        graph.set_node_tag(b"12", b"KC:i:42").unwrap();
+        let mut history = History::new();
+
+        history
+            .apply(&mut graph, EditOperation::RemoveNode { id: "12".into() })
+            .unwrap();
+        assert!(graph.node_id(b"12").is_err());
+        assert_eq!(graph.graph.edge_count(), 1);
+
+        let undone = history.undo(&mut graph).unwrap().unwrap();
+        assert_eq!(undone.operations[0].to_string(), "REMOVE NODE 12");
+        assert_eq!(graph.graph.edge_count(), 3);
+        assert_eq!(
+            get_node_tag(&graph, b"12", b"KC").unwrap().to_string(),
+            "KC:i:42"
+        );
+
+        history.redo(&mut graph).unwrap();
+        assert!(graph.node_id(b"12").is_err());
+        assert_eq!(history.len(), 1);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn cannot_redo_after_new_operation() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        let mut history = History::new();
+
+        history
+            .apply(
+                &mut graph,
+                EditOperation::ModifyNode {
+                    id: "11".into(),
+                    sequence: "GATTACA".into(),
+                },
+            )
+            .unwrap();
+        history.undo(&mut graph).unwrap();
+        assert!(history.can_redo());
+        history
+            .apply(&mut graph, EditOperation::RemovePath { name: "15".into() })
+            .unwrap();
+        assert!(!history.can_redo());
+        assert!(history.redo(&mut graph).unwrap().is_none());
+        history.undo(&mut graph).unwrap();
+        assert!(history.undo(&mut graph).unwrap().is_none());
+        assert!(graph.has_path(b"15"));
+    }
+}
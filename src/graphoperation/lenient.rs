@@ -0,0 +1,561 @@
+/// load a graph from a messy file, skipping or repairing the records that cannot be loaded
+use bstr::{BString, ByteSlice};
+use gfa2::{
+    gfa1::{Line as GFA1Line, GFA},
+    gfa2::{Line as GFA2Line, GFA2},
+    parser_gfa1::GFAParser,
+    parser_gfa2::GFA2Parser,
+    tag::OptionalFields,
+};
+use std::{collections::HashSet, convert::TryFrom, fmt, io::Read};
+
+use super::*;
+
+/// What is wrong with a line of a file loaded in lenient mode, and what has been done with it
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadWarningKind {
+    /// the record type does not exist in the version of the file, the line is skipped
+    UnknownRecordType,
+    /// the record cannot be parsed, the line is skipped
+    InvalidRecord(String),
+    /// an optional field does not follow the specification, it's dropped and the record is kept
+    InvalidTag(String),
+    /// a segment with the same name has been read before, the line is skipped
+    DuplicateSegment(String),
+    /// a path (or ordered group) with the same name has been read before, the line is skipped
+    DuplicatePath(String),
+    /// the record refers to a segment that is not in the file, the line is skipped
+    MissingSegment(String),
+}
+
+impl fmt::Display for LoadWarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use LoadWarningKind as LW;
+        match self {
+            LW::UnknownRecordType => write!(f, "unknown record type, skipped"),
+            LW::InvalidRecord(why) => write!(f, "cannot be parsed ({}), skipped", why),
+            LW::InvalidTag(tag) => write!(f, "the optional field ({}) is not valid, dropped", tag),
+            LW::DuplicateSegment(name) => {
+                write!(f, "the segment ({}) has already been read, skipped", name)
+            }
+            LW::DuplicatePath(name) => {
+                write!(f, "the path ({}) has already been read, skipped", name)
+            }
+            LW::MissingSegment(name) => {
+                write!(f, "the segment ({}) is not in the file, skipped", name)
+            }
+        }
+    }
+}
+
+/// A line of a file loaded in lenient mode that has been skipped or repaired
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadWarning {
+    /// the line of the file (starting from 1)
+    pub line: usize,
+    /// the type of the record (```S```, ```L```, ```E```...)
+    pub record_type: String,
+    pub kind: LoadWarningKind,
+}
+
+impl LoadWarning {
+    /// Tell if the record has been kept, without the parts that cannot be loaded
+    pub fn is_repaired(&self) -> bool {
+        matches!(self.kind, LoadWarningKind::InvalidTag(_))
+    }
+}
+
+impl fmt::Display for LoadWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Line {} ({} record): {}",
+            self.line, self.record_type, self.kind
+        )
+    }
+}
+
+// the fields that come before the optional ones in every record type of a version
+fn required_fields(version: GFAVersion, record_type: &[u8]) -> Option<usize> {
+    match (version, record_type) {
+        (_, b"H") => Some(1),
+        (GFAVersion::GFA1, b"S") => Some(3),
+        (GFAVersion::GFA1, b"L") => Some(6),
+        (GFAVersion::GFA1, b"C") | (GFAVersion::GFA1, b"W") => Some(7),
+        (GFAVersion::GFA1, b"P") => Some(4),
+        (GFAVersion::GFA2, b"S") => Some(4),
+        (GFAVersion::GFA2, b"F") => Some(8),
+        (GFAVersion::GFA2, b"E") => Some(9),
+        (GFAVersion::GFA2, b"G") => Some(6),
+        (GFAVersion::GFA2, b"O") | (GFAVersion::GFA2, b"U") => Some(3),
+        _ => None,
+    }
+}
+
+// check that every name is the one of a segment of the file
+fn check_names<'a>(
+    names: impl IntoIterator<Item = &'a [u8]>,
+    segments: &HashSet<BString>,
+) -> Result<(), LoadWarningKind> {
+    for name in names {
+        if !segments.contains(name.as_bstr()) {
+            return Err(LoadWarningKind::MissingSegment(
+                name.to_str_lossy().to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// check that every reference (```11+```) has an orientation and refers to a segment of the file
+fn check_references<'a>(
+    references: impl IntoIterator<Item = &'a [u8]>,
+    segments: &HashSet<BString>,
+) -> Result<(), LoadWarningKind> {
+    for reference in references {
+        let id = OrientedId::try_from(reference)
+            .map_err(|why| LoadWarningKind::InvalidRecord(why.to_string()))?;
+        check_names(vec![id.name.as_bytes()], segments)?;
+    }
+    Ok(())
+}
+
+fn check_new_path(paths: &mut HashSet<BString>, name: &[u8]) -> Result<(), LoadWarningKind> {
+    if paths.insert(BString::from(name)) {
+        Ok(())
+    } else {
+        Err(LoadWarningKind::DuplicatePath(
+            name.to_str_lossy().to_string(),
+        ))
+    }
+}
+
+// collect the warnings of the lines read, in the order of the file
+struct LenientReader {
+    version: GFAVersion,
+    warnings: Vec<LoadWarning>,
+}
+
+impl LenientReader {
+    fn new(version: GFAVersion) -> Self {
+        LenientReader {
+            version,
+            warnings: vec![],
+        }
+    }
+
+    fn warn(&mut self, line: usize, record_type: &str, kind: LoadWarningKind) {
+        self.warnings.push(LoadWarning {
+            line,
+            record_type: record_type.to_string(),
+            kind,
+        });
+    }
+
+    // the line without the optional fields that cannot be loaded,
+    // None when the record type does not exist in the version read
+    fn repaired(&mut self, line: usize, text: &[u8]) -> Option<Vec<u8>> {
+        let fields: Vec<&[u8]> = text.split_str("\t").collect();
+        let record_type = record_type(text);
+        let required = match required_fields(self.version, fields[0]) {
+            Some(required) => required,
+            None => {
+                self.warn(line, &record_type, LoadWarningKind::UnknownRecordType);
+                return None;
+            }
+        };
+        let mut kept = vec![];
+        for (i, field) in fields.into_iter().enumerate() {
+            if i < required || parse_tag(field).is_ok() {
+                kept.push(field);
+            } else {
+                let tag = field.to_str_lossy().to_string();
+                self.warn(line, &record_type, LoadWarningKind::InvalidTag(tag));
+            }
+        }
+        Some(bstr::join("\t", kept))
+    }
+
+    fn finish(mut self) -> Vec<LoadWarning> {
+        // the records are checked by type, so the warnings are sorted back by line
+        self.warnings.sort_by_key(|warning| warning.line);
+        self.warnings
+    }
+}
+
+/// Function that reads a ```GFA1``` from any reader (a file, the standard input...)
+/// skipping or repairing the records that cannot be loaded: unknown record types,
+/// records that cannot be parsed, optional fields not valid, segments and paths read twice,
+/// links, containments, paths and walks that refer to segments not in the file.\
+/// The graph is returned together with a warning for every one of them
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let (graph, warnings) = gfa1_to_handlegraph_from_reader_lenient(std::io::stdin()).unwrap();
+/// for warning in warnings.iter() {
+///     println!("{}", warning);
+/// }
+/// ```
+pub fn gfa1_to_handlegraph_from_reader_lenient<R: Read>(
+    reader: R,
+) -> Result<(GFAGraph, Vec<LoadWarning>), GraphOperationError> {
+    let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+    let mut lenient = LenientReader::new(GFAVersion::GFA1);
+    let mut records = vec![];
+    let mut walks = vec![];
+    for_each_line(reader, |line, text| {
+        let text = match lenient.repaired(line, text) {
+            Some(text) => text,
+            None => return Ok(()),
+        };
+        // the walks are not known by the parser
+        if text.starts_with(b"W\t") {
+            match WalkRecord::parse(&text) {
+                Ok(walk) => walks.push((line, walk)),
+                Err(why) => {
+                    lenient.warn(line, "W", LoadWarningKind::InvalidRecord(why.to_string()))
+                }
+            }
+            return Ok(());
+        }
+        match parser.parse_gfa_line(&text) {
+            Ok(record) => records.push((line, record)),
+            Err(why) => lenient.warn(
+                line,
+                &record_type(&text),
+                LoadWarningKind::InvalidRecord(why.to_string()),
+            ),
+        }
+        Ok(())
+    })?;
+
+    // the records can refer to segments defined later in the file
+    let mut gfa: GFA<BString, OptionalFields> = GFA::new();
+    let mut segments = HashSet::new();
+    for (line, record) in records.iter() {
+        if let GFA1Line::Segment(segment) = record {
+            if segments.insert(segment.name.clone()) {
+                gfa.segments.push(segment.clone());
+            } else {
+                let name = segment.name.to_string();
+                lenient.warn(*line, "S", LoadWarningKind::DuplicateSegment(name));
+            }
+        }
+    }
+    let mut paths = HashSet::new();
+    for (line, record) in records.into_iter() {
+        match record {
+            GFA1Line::Header(header) => gfa.header = header,
+            GFA1Line::Segment(_) => (),
+            GFA1Line::Link(link) => {
+                let names = vec![link.from_segment.as_bytes(), link.to_segment.as_bytes()];
+                match check_names(names, &segments) {
+                    Ok(()) => gfa.links.push(link),
+                    Err(kind) => lenient.warn(line, "L", kind),
+                }
+            }
+            GFA1Line::Containment(containment) => {
+                let names = vec![
+                    containment.container_name.as_bytes(),
+                    containment.contained_name.as_bytes(),
+                ];
+                match check_names(names, &segments) {
+                    Ok(()) => gfa.containments.push(containment),
+                    Err(kind) => lenient.warn(line, "C", kind),
+                }
+            }
+            GFA1Line::Path(path) => {
+                let steps = path
+                    .segment_names
+                    .split_str(",")
+                    .filter(|step| !step.is_empty());
+                match check_references(steps, &segments)
+                    .and_then(|_| check_new_path(&mut paths, &path.path_name))
+                {
+                    Ok(()) => gfa.paths.push(path),
+                    Err(kind) => lenient.warn(line, "P", kind),
+                }
+            }
+        }
+    }
+
+    let mut graph = GFAGraph::from_gfa(&gfa)?;
+    for (line, walk) in walks.iter() {
+        let steps = walk.steps.iter().map(|step| step.as_bytes());
+        match check_references(steps, &segments)
+            .and_then(|_| check_new_path(&mut paths, &walk.walk.path_name()))
+        {
+            Ok(()) => graph.add_walk_record(walk)?,
+            Err(kind) => lenient.warn(*line, "W", kind),
+        }
+    }
+    Ok((graph, lenient.finish()))
+}
+
+/// Function that reads a ```GFA2``` from any reader (a file, the standard input...)
+/// skipping or repairing the records that cannot be loaded: unknown record types,
+/// records that cannot be parsed, optional fields not valid, segments and ordered groups read twice,
+/// fragments, edges, gaps and ordered groups that refer to segments not in the file.\
+/// The graph is returned together with a warning for every one of them
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let (graph, warnings) = gfa2_to_handlegraph_from_reader_lenient(std::io::stdin()).unwrap();
+/// ```
+pub fn gfa2_to_handlegraph_from_reader_lenient<R: Read>(
+    reader: R,
+) -> Result<(GFAGraph, Vec<LoadWarning>), GraphOperationError> {
+    let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    let mut lenient = LenientReader::new(GFAVersion::GFA2);
+    let mut records = vec![];
+    for_each_line(reader, |line, text| {
+        if let Some(text) = lenient.repaired(line, text) {
+            match parser.parse_gfa_line(&text) {
+                Ok(record) => records.push((line, record)),
+                Err(why) => lenient.warn(
+                    line,
+                    &record_type(&text),
+                    LoadWarningKind::InvalidRecord(why.to_string()),
+                ),
+            }
+        }
+        Ok(())
+    })?;
+
+    // the records can refer to segments defined later in the file
+    let mut gfa2: GFA2<BString, OptionalFields> = GFA2::new();
+    let mut segments = HashSet::new();
+    let mut edge_ids = HashSet::new();
+    for (line, record) in records.iter() {
+        match record {
+            GFA2Line::Segment(segment) => {
+                if segments.insert(segment.id.clone()) {
+                    gfa2.segments.push(segment.clone());
+                } else {
+                    let name = segment.id.to_string();
+                    lenient.warn(*line, "S", LoadWarningKind::DuplicateSegment(name));
+                }
+            }
+            GFA2Line::Edge(edge) if edge.id != "*" => {
+                edge_ids.insert(edge.id.clone());
+            }
+            _ => (),
+        }
+    }
+    let mut paths = HashSet::new();
+    for (line, record) in records.into_iter() {
+        match record {
+            GFA2Line::Header(header) => gfa2.headers.push(header),
+            GFA2Line::Segment(_) => (),
+            GFA2Line::Fragment(fragment) => {
+                match check_names(vec![fragment.id.as_bytes()], &segments) {
+                    Ok(()) => gfa2.fragments.push(fragment),
+                    Err(kind) => lenient.warn(line, "F", kind),
+                }
+            }
+            GFA2Line::Edge(edge) => {
+                let references = vec![edge.sid1.as_bytes(), edge.sid2.as_bytes()];
+                match check_references(references, &segments) {
+                    Ok(()) => gfa2.edges.push(edge),
+                    Err(kind) => lenient.warn(line, "E", kind),
+                }
+            }
+            GFA2Line::Gap(gap) => {
+                let references = vec![gap.sid1.as_bytes(), gap.sid2.as_bytes()];
+                match check_references(references, &segments) {
+                    Ok(()) => gfa2.gaps.push(gap),
+                    Err(kind) => lenient.warn(line, "G", kind),
+                }
+            }
+            GFA2Line::GroupO(group) => {
+                // the edges between the steps are not steps themselves
+                let steps = group
+                    .var_field
+                    .split_str(" ")
+                    .filter(|step| !step.is_empty() && !refers_to_edge(step, &edge_ids));
+                match check_references(steps, &segments)
+                    .and_then(|_| check_new_path(&mut paths, &group.id))
+                {
+                    Ok(()) => gfa2.groups_o.push(group),
+                    Err(kind) => lenient.warn(line, "O", kind),
+                }
+            }
+            GFA2Line::GroupU(group) => gfa2.groups_u.push(group),
+        }
+    }
+    Ok((GFAGraph::from_gfa2(&gfa2)?, lenient.finish()))
+}
+
+/// Function that reads a ```GFA1``` file in lenient mode
+/// (see ```gfa1_to_handlegraph_from_reader_lenient```)
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let (graph, warnings) = gfa1_to_handlegraph_lenient("./tests/gfa1_files/lil.gfa".to_string()).unwrap();
+/// ```
+pub fn gfa1_to_handlegraph_lenient(
+    path: String,
+) -> Result<(GFAGraph, Vec<LoadWarning>), GraphOperationError> {
+    match std::fs::File::open(&path) {
+        Ok(file) => {
+            let (mut graph, warnings) =
+                gfa1_to_handlegraph_from_reader_lenient(file).map_err(|why| why.in_file(&path))?;
+            graph.source = Some(path.into());
+            Ok((graph, warnings))
+        }
+        Err(why) => Err(GraphOperationError::Io(path, why)),
+    }
+}
+
+/// Function that reads a ```GFA2``` file in lenient mode
+/// (see ```gfa2_to_handlegraph_from_reader_lenient```)
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let (graph, warnings) = gfa2_to_handlegraph_lenient("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+/// ```
+pub fn gfa2_to_handlegraph_lenient(
+    path: String,
+) -> Result<(GFAGraph, Vec<LoadWarning>), GraphOperationError> {
+    match std::fs::File::open(&path) {
+        Ok(file) => {
+            let (mut graph, warnings) =
+                gfa2_to_handlegraph_from_reader_lenient(file).map_err(|why| why.in_file(&path))?;
+            graph.source = Some(path.into());
+            Ok((graph, warnings))
+        }
+        Err(why) => Err(GraphOperationError::Io(path, why)),
+    }
+}
+
+/// Function that reads a ```GFA1``` or ```GFA2``` from any reader in lenient mode,
+/// telling its version from its content like ```load_graph_from_reader```
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let (version, graph, warnings) = load_graph_from_reader_lenient(std::io::stdin()).unwrap();
+/// ```
+pub fn load_graph_from_reader_lenient<R: Read>(
+    reader: R,
+) -> Result<(GFAVersion, GFAGraph, Vec<LoadWarning>), GraphOperationError> {
+    load_graph_lenient_with_fallback(reader, None)
+}
+
+/// Function that reads a ```GFA1``` or ```GFA2``` file in lenient mode,
+/// telling its version like ```load_graph```
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let (version, graph, warnings) = load_graph_lenient("./tests/gfa1_files/lil.gfa".to_string()).unwrap();
+/// ```
+pub fn load_graph_lenient(
+    path: String,
+) -> Result<(GFAVersion, GFAGraph, Vec<LoadWarning>), GraphOperationError> {
+    match std::fs::File::open(&path) {
+        Ok(file) => {
+            let (version, mut graph, warnings) =
+                load_graph_lenient_with_fallback(file, GFAVersion::from_extension(&path))
+                    .map_err(|why| why.in_file(&path))?;
+            graph.source = Some(path.into());
+            Ok((version, graph, warnings))
+        }
+        Err(why) => Err(GraphOperationError::Io(path, why)),
+    }
+}
+
+fn load_graph_lenient_with_fallback<R: Read>(
+    reader: R,
+    fallback: Option<GFAVersion>,
+) -> Result<(GFAVersion, GFAGraph, Vec<LoadWarning>), GraphOperationError> {
+    let (version, reader) = with_detected_version(reader, fallback)?;
+    let (graph, warnings) = match version {
+        GFAVersion::GFA1 => gfa1_to_handlegraph_from_reader_lenient(reader)?,
+        GFAVersion::GFA2 => gfa2_to_handlegraph_from_reader_lenient(reader)?,
+    };
+    Ok((version, graph, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use handlegraph2::{handle::Handle, handlegraph::HandleGraph};
+
+    #[test]
+    fn can_load_a_messy_gfa1_file() {
+        let gfa1 = b"H\tVN:Z:1.0\nS\t11\tACCTT\tKC:Z:wrong\tRC:i:4\nS\t12\tTCAAGG\n\
+            S\t11\tGGG\nX\tunknown\nL\t11\t+\t12\t-\t0M\nL\t11\t+\t13\t+\t0M\n\
+            P\tp1\t11+,12-\t*\nP\tp1\t12+\t*\nP\tp2\t11+,14+\t*\nW\tNA12878\t1\tchr1\t0\t5\t>11<13\n";
+        // the unknown record type is skipped by the strict loader too, a malformed link is not
+        let malformed = [&gfa1[..], b"L\t11\t+\n"].concat();
+        assert!(gfa1_to_handlegraph_from_reader(&malformed[..]).is_err());
+
+        let (version, graph, warnings) = load_graph_from_reader_lenient(&gfa1[..]).unwrap();
+        assert_eq!(version, GFAVersion::GFA1);
+        assert_eq!(
+            graph
+                .graph
+                .sequence(Handle::pack(graph.node_id(b"11").unwrap(), false)),
+            b"ACCTT"
+        );
+        assert_eq!(
+            graph
+                .get_segment_tags(graph.node_id(b"11").unwrap())
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(graph.existing_edge(b"11+", b"12-").is_ok());
+        assert!(graph.node_id(b"13").is_err());
+        assert!(graph.has_path(b"p1"));
+        assert!(!graph.has_path(b"p2"));
+        assert!(graph.walks.is_empty());
+
+        let lines: Vec<usize> = warnings.iter().map(|warning| warning.line).collect();
+        assert_eq!(lines, [2, 4, 5, 7, 9, 10, 11]);
+        assert!(warnings[0].is_repaired());
+        assert_eq!(
+            warnings[1].kind,
+            LoadWarningKind::DuplicateSegment("11".to_string())
+        );
+        assert_eq!(warnings[2].kind, LoadWarningKind::UnknownRecordType);
+        assert_eq!(
+            warnings[3].kind,
+            LoadWarningKind::MissingSegment("13".to_string())
+        );
+        assert_eq!(
+            warnings[4].kind,
+            LoadWarningKind::DuplicatePath("p1".to_string())
+        );
+        assert_eq!(
+            warnings[6].to_string(),
+            "Line 11 (W record): the segment (13) is not in the file, skipped"
+        );
+    }
+
+    #[test]
+    fn can_load_a_messy_gfa2_file() {
+        let gfa2 = b"H\tVN:Z:2.0\nS\t11\t5\tACCTT\nS\t12\t6\tTCAAGG\n\
+            E\t*\t11+\t12-\t0\t0$\t0\t0$\t0M\nE\t*\t11+\t13+\t0\t0$\t0\t0$\t0M\n\
+            E\t*\t11\t12+\t0\t0$\t0\t0$\t0M\nL\t11\t+\t12\t-\t0M\n\
+            O\t14\t11+ 12-\nO\t15\t11+ 13+\n";
+        let (graph, warnings) = gfa2_to_handlegraph_from_reader_lenient(&gfa2[..]).unwrap();
+        assert!(graph.existing_edge(b"11+", b"12-").is_ok());
+        assert!(graph.has_path(b"14"));
+        assert!(!graph.has_path(b"15"));
+
+        let lines: Vec<usize> = warnings.iter().map(|warning| warning.line).collect();
+        assert_eq!(lines, [5, 6, 7, 9]);
+        assert!(matches!(
+            warnings[1].kind,
+            LoadWarningKind::InvalidRecord(_)
+        ));
+        assert_eq!(warnings[2].kind, LoadWarningKind::UnknownRecordType);
+        assert!(warnings.iter().all(|warning| !warning.is_repaired()));
+    }
+}
@@ -0,0 +1,110 @@
+/// keep the association between the names of the segments and the ids of the nodes
+use bstr::{BString, ByteSlice};
+use handlegraph2::handle::NodeId;
+use std::collections::HashMap;
+
+/// Bidirectional table between the names of the segments of a GFA file
+/// and the ```NodeId``` of the nodes of the graph.\
+/// A numeric name keeps its value as id (if it's still free), every other name
+/// (```utg000123l```, ```chr1_ctg5```...) gets the first id not used yet
+#[derive(Debug, Clone)]
+pub struct NameMap {
+    ids: HashMap<BString, NodeId>,
+    names: HashMap<NodeId, BString>,
+    next_id: u64,
+}
+
+impl Default for NameMap {
+    fn default() -> Self {
+        NameMap::new()
+    }
+}
+
+fn numeric_id(name: &[u8]) -> Option<u64> {
+    if name.is_empty() || !name.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    name.to_str().ok()?.parse::<u64>().ok()
+}
+
+impl NameMap {
+    pub fn new() -> Self {
+        NameMap {
+            ids: HashMap::new(),
+            names: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Return the id associated to a name, if any
+    pub fn get_id(&self, name: &[u8]) -> Option<NodeId> {
+        self.ids.get(name.as_bstr()).copied()
+    }
+
+    /// Return the name associated to an id, if any
+    pub fn get_name(&self, id: NodeId) -> Option<&BString> {
+        self.names.get(&id)
+    }
+
+    /// Return the id associated to a name, creating a new association if the name is new
+    pub fn insert(&mut self, name: &[u8]) -> NodeId {
+        if let Some(id) = self.get_id(name) {
+            return id;
+        }
+        let id = match numeric_id(name) {
+            Some(id) if !self.names.contains_key(&NodeId::from(id)) => {
+                self.next_id = self.next_id.max(id + 1);
+                NodeId::from(id)
+            }
+            _ => {
+                while self.names.contains_key(&NodeId::from(self.next_id)) {
+                    self.next_id += 1;
+                }
+                self.next_id += 1;
+                NodeId::from(self.next_id - 1)
+            }
+        };
+        self.ids.insert(BString::from(name), id);
+        self.names.insert(id, BString::from(name));
+        id
+    }
+
+    /// Remove the association of a name returning its id, if any
+    pub fn remove(&mut self, name: &[u8]) -> Option<NodeId> {
+        let id = self.ids.remove(name.as_bstr())?;
+        self.names.remove(&id);
+        Some(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_map_names_and_ids() {
+        let mut names = NameMap::new();
+        let utg = names.insert(b"utg000123l");
+        let eleven = names.insert(b"11");
+        let ctg = names.insert(b"chr1_ctg5");
+
+        assert_eq!(eleven, NodeId::from(11));
+        assert_eq!(names.insert(b"utg000123l"), utg);
+        assert_ne!(utg, ctg);
+        assert_ne!(ctg, eleven);
+        assert_eq!(names.get_name(ctg).unwrap(), "chr1_ctg5");
+
+        // the id of "11" is taken, so "011" gets a new one
+        assert_ne!(names.insert(b"011"), eleven);
+        assert_eq!(names.remove(b"11"), Some(eleven));
+        assert!(names.get_name(eleven).is_none());
+    }
+}
@@ -0,0 +1,1174 @@
+/// describe the edit operations as values, so they can be applied, inverted and recorded
+use bstr::{BString, ByteSlice};
+use gfa2::gfa2::orientation::Orientation;
+use handlegraph2::{
+    handle::{Direction, Edge, Handle},
+    handlegraph::HandleGraph,
+};
+use std::{collections::HashSet, convert::TryFrom, fmt, str::FromStr};
+
+use super::error::*;
+use super::gfagraph::*;
+use super::orientedid::*;
+use super::overlap::*;
+use super::record::*;
+use super::tag::*;
+use super::walk::*;
+
+/// The element of the graph an optional field belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagTarget {
+    Node(BString),
+    Link(BString, BString),
+    Path(BString),
+}
+
+/// A single edit on a ```GFAGraph```.\
+/// The nodes are referenced by their segment name and the links
+/// by the segment names with their orientation (```utg1+```)
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditOperation {
+    AddNode {
+        id: BString,
+        sequence: Option<BString>,
+        /// the length of a segment without sequence (```*```), as read from a ```GFA2``` file
+        length: Option<usize>,
+    },
+    AddLink {
+        from: BString,
+        to: BString,
+        /// the CIGAR of the overlap, a blunt link when not given
+        overlap: Option<BString>,
+    },
+    /// A link with the positions of its ends, as the edges of a ```GFA2``` file
+    AddEdge {
+        /// ```*``` for an edge without id
+        id: BString,
+        from: BString,
+        to: BString,
+        overlap: Overlap,
+    },
+    AddPath {
+        name: BString,
+        steps: Vec<BString>,
+        /// how the consecutive steps are checked against the links of the graph
+        links: PathLinks,
+    },
+    RemoveNode {
+        id: BString,
+    },
+    RemoveLink {
+        from: BString,
+        to: BString,
+    },
+    RemovePath {
+        name: BString,
+    },
+    ModifyNode {
+        id: BString,
+        sequence: BString,
+    },
+    ModifyLink {
+        from: BString,
+        to: BString,
+        new_from: Option<BString>,
+        new_to: Option<BString>,
+        /// the new CIGAR of the overlap, the old one is kept when not given
+        overlap: Option<BString>,
+    },
+    ModifyPath {
+        name: BString,
+        steps: Vec<BString>,
+    },
+    SetTag {
+        target: TagTarget,
+        field: BString,
+    },
+    RemoveTag {
+        target: TagTarget,
+        tag: BString,
+    },
+    AddContainment {
+        containment: Containment,
+    },
+    RemoveContainment {
+        container: BString,
+        contained: BString,
+    },
+    SetWalk {
+        name: BString,
+        walk: Walk,
+    },
+    RemoveWalk {
+        name: BString,
+    },
+    AddFragment {
+        fragment: Fragment,
+    },
+    RemoveFragment {
+        segment: BString,
+        external: BString,
+        /// needed only when the external sequence is aligned more than once to the segment
+        positions: Option<[BString; 4]>,
+    },
+    AddGap {
+        gap: Gap,
+    },
+    RemoveGap {
+        gap: GapReference,
+    },
+    AddGroup {
+        group: UnorderedGroup,
+    },
+    RemoveGroup {
+        id: BString,
+    },
+    ModifyGroup {
+        id: BString,
+        members: Vec<BString>,
+    },
+}
+
+impl fmt::Display for TagTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TagTarget::Node(id) => write!(f, "NODE {}", id),
+            TagTarget::Link(from, to) => write!(f, "LINK {} {}", from, to),
+            TagTarget::Path(name) => write!(f, "PATH {}", name),
+        }
+    }
+}
+
+// the fields of a record followed by its tags
+fn with_tags(fields: Vec<String>, tags: &gfa2::tag::OptionalFields) -> String {
+    let mut record = fields.join(" ");
+    for field in tags.iter() {
+        record.push_str(&format!(" {}", quoted(&field.to_string())));
+    }
+    record
+}
+
+// an optional last argument
+fn optional(value: &Option<BString>) -> String {
+    match value {
+        Some(value) => format!(" {}", value),
+        None => String::new(),
+    }
+}
+
+fn or_star(value: &Option<BString>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "*".to_string(),
+    }
+}
+
+fn join(steps: &[BString]) -> String {
+    let steps: Vec<String> = steps.iter().map(|step| step.to_string()).collect();
+    steps.join(" ")
+}
+
+// the same grammar used by the interactive command line
+impl fmt::Display for EditOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use EditOperation as EO;
+        match self {
+            EO::AddNode {
+                id,
+                sequence,
+                length,
+            } => {
+                let length = length.map(|length| BString::from(length.to_string()));
+                write!(
+                    f,
+                    "ADD NODE {} {}{}",
+                    id,
+                    or_star(sequence),
+                    optional(&length)
+                )
+            }
+            EO::AddLink { from, to, overlap } => {
+                write!(f, "ADD LINK {} {}{}", from, to, optional(overlap))
+            }
+            EO::AddEdge {
+                id,
+                from,
+                to,
+                overlap,
+            } => {
+                write!(f, "ADD EDGE {} {} {}", id, from, to)?;
+                for position in overlap.positions.iter().flatten() {
+                    write!(f, " {}", position)?;
+                }
+                write!(f, " {}", overlap.alignment)
+            }
+            EO::AddPath { name, steps, links } => {
+                write!(f, "ADD PATH {} {}{}", name, join(steps), links)
+            }
+            EO::RemoveNode { id } => write!(f, "REMOVE NODE {}", id),
+            EO::RemoveLink { from, to } => write!(f, "REMOVE LINK {} {}", from, to),
+            EO::RemovePath { name } => write!(f, "REMOVE PATH {}", name),
+            EO::ModifyNode { id, sequence } => write!(f, "MODIFY NODE {} {}", id, sequence),
+            EO::ModifyLink {
+                from,
+                to,
+                new_from,
+                new_to,
+                overlap,
+            } => write!(
+                f,
+                "MODIFY LINK {} {} {} {}{}",
+                from,
+                to,
+                or_star(new_from),
+                or_star(new_to),
+                optional(overlap)
+            ),
+            EO::ModifyPath { name, steps } => write!(f, "MODIFY PATH {} {}", name, join(steps)),
+            EO::SetTag { target, field } => {
+                write!(f, "SET TAG {} {}", target, quoted(&field.to_string()))
+            }
+            EO::RemoveTag { target, tag } => write!(f, "REMOVE TAG {} {}", target, tag),
+            EO::AddContainment { containment } => write!(
+                f,
+                "ADD CONTAINMENT {} {} {}",
+                containment.container,
+                containment.contained,
+                with_tags(
+                    vec![containment.pos.to_string(), containment.overlap.to_string()],
+                    &containment.tags
+                )
+            ),
+            EO::RemoveContainment {
+                container,
+                contained,
+            } => write!(f, "REMOVE CONTAINMENT {} {}", container, contained),
+            EO::SetWalk { name, walk } => {
+                write!(f, "SET WALK {} {}", name, walk.fields().join(" "))
+            }
+            EO::RemoveWalk { name } => write!(f, "REMOVE WALK {}", name),
+            EO::AddFragment { fragment } => write!(
+                f,
+                "ADD FRAGMENT {}",
+                with_tags(fragment.fields(), &fragment.tags)
+            ),
+            EO::RemoveFragment {
+                segment,
+                external,
+                positions,
+            } => {
+                write!(f, "REMOVE FRAGMENT {} {}", segment, external)?;
+                match positions {
+                    Some(positions) => {
+                        for position in positions.iter() {
+                            write!(f, " {}", position)?;
+                        }
+                        Ok(())
+                    }
+                    None => Ok(()),
+                }
+            }
+            EO::AddGap { gap } => write!(f, "ADD GAP {}", with_tags(gap.fields(), &gap.tags)),
+            EO::RemoveGap { gap } => write!(f, "REMOVE GAP {}", gap),
+            EO::AddGroup { group } => {
+                write!(f, "ADD GROUP {}", with_tags(group.fields(), &group.tags))
+            }
+            EO::RemoveGroup { id } => write!(f, "REMOVE GROUP {}", id),
+            EO::ModifyGroup { id, members } => {
+                write!(f, "MODIFY GROUP {} {}", id, join(members))
+            }
+        }
+    }
+}
+
+fn value(token: &str) -> Option<BString> {
+    if token == "*" {
+        None
+    } else {
+        Some(BString::from(token))
+    }
+}
+
+// check that a token is a segment reference with orientation
+fn oriented(token: &str) -> Result<BString, GraphOperationError> {
+    OrientedId::try_from(token.as_bytes())?;
+    Ok(BString::from(token))
+}
+
+fn parse_tags(fields: &[&str]) -> Result<gfa2::tag::OptionalFields, GraphOperationError> {
+    fields
+        .iter()
+        .map(|field| parse_tag(field.as_bytes()))
+        .collect()
+}
+
+fn parse_target<'a>(tokens: &'a [&'a str]) -> Option<(TagTarget, &'a [&'a str])> {
+    match tokens {
+        [kind, id, rest @ ..] if kind.eq_ignore_ascii_case("NODE") => {
+            Some((TagTarget::Node(BString::from(*id)), rest))
+        }
+        [kind, from, to, rest @ ..] if kind.eq_ignore_ascii_case("LINK") => Some((
+            TagTarget::Link(BString::from(*from), BString::from(*to)),
+            rest,
+        )),
+        [kind, name, rest @ ..] if kind.eq_ignore_ascii_case("PATH") => {
+            Some((TagTarget::Path(BString::from(*name)), rest))
+        }
+        _ => None,
+    }
+}
+
+// split an operation in its tokens: a token that starts with a double quote goes on
+// until the closing one, with \" and \\ standing for a double quote and a backslash
+fn tokenize(operation: &str) -> Option<Vec<String>> {
+    let mut tokens = vec![];
+    let mut chars = operation.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut token = String::new();
+        match chars.peek() {
+            None => return Some(tokens),
+            Some('"') => {
+                chars.next();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => token.push(chars.next()?),
+                        c => token.push(c),
+                    }
+                }
+                // the closing quote ends the token
+                if matches!(chars.peek(), Some(c) if !c.is_whitespace()) {
+                    return None;
+                }
+            }
+            Some(_) => {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    token.push(c);
+                }
+            }
+        }
+        tokens.push(token);
+    }
+}
+
+// an optional field with whitespaces (allowed in a Z or J value) is written
+// between double quotes, so that it's read back as a single token
+fn quoted(field: &str) -> String {
+    if field.contains(char::is_whitespace) {
+        format!("\"{}\"", field.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Read an operation written with the same grammar used by the interactive command line
+/// (```ADD NODE 42 ACGT```, ```MODIFY LINK 11+ 12- * 13+```, ```SET TAG PATH 14 KC:i:1```...).\
+/// The keywords are case insensitive and ```*``` stands for a missing value.
+/// An optional field with spaces is written between double quotes (```SET TAG NODE 11 "CO:Z:two words"```)
+impl FromStr for EditOperation {
+    type Err = GraphOperationError;
+
+    fn from_str(operation: &str) -> Result<Self, Self::Err> {
+        use EditOperation as EO;
+
+        let invalid = || GraphOperationError::InvalidOperation(operation.trim().to_string());
+        let tokens = tokenize(operation).ok_or_else(invalid)?;
+        let tokens: Vec<&str> = tokens.iter().map(|token| token.as_str()).collect();
+        let upper: Vec<String> = tokens
+            .iter()
+            .take(2)
+            .map(|token| token.to_ascii_uppercase())
+            .collect();
+        let keywords: Vec<&str> = upper.iter().map(|token| token.as_str()).collect();
+        let steps = |steps: &[&str]| -> Vec<BString> {
+            steps.iter().map(|step| BString::from(*step)).collect()
+        };
+
+        let edit = match (keywords.as_slice(), &tokens[keywords.len()..]) {
+            (["ADD", "NODE"], [id, sequence]) => EO::AddNode {
+                id: BString::from(*id),
+                sequence: value(sequence),
+                length: None,
+            },
+            // a segment without sequence, with its length
+            (["ADD", "NODE"], [id, sequence, length]) => EO::AddNode {
+                id: BString::from(*id),
+                sequence: Some(BString::from(*sequence)),
+                length: Some(length.parse().map_err(|_| invalid())?),
+            },
+            (["ADD", "NODE"], [id]) => EO::AddNode {
+                id: BString::from(*id),
+                sequence: None,
+                length: None,
+            },
+            (["ADD", "LINK"], [from, to, overlap @ ..]) if overlap.len() <= 1 => EO::AddLink {
+                from: oriented(from)?,
+                to: oriented(to)?,
+                overlap: overlap.first().map(|overlap| BString::from(*overlap)),
+            },
+            (["ADD", "EDGE"], [id, from, to, beg1, end1, beg2, end2, alignment]) => EO::AddEdge {
+                id: BString::from(*id),
+                from: oriented(from)?,
+                to: oriented(to)?,
+                overlap: Overlap::with_positions(
+                    alignment.as_bytes(),
+                    [
+                        BString::from(*beg1),
+                        BString::from(*end1),
+                        BString::from(*beg2),
+                        BString::from(*end2),
+                    ],
+                )
+                .map_err(|_| invalid())?,
+            },
+            (["ADD", "PATH"], [name, rest @ ..]) => {
+                // the steps can be followed by how their links are checked
+                let (links, rest) = match rest.split_last() {
+                    Some((last, steps)) if last.eq_ignore_ascii_case("CREATE-LINKS") => {
+                        (PathLinks::Create, steps)
+                    }
+                    Some((last, steps)) if last.eq_ignore_ascii_case("UNCHECKED") => {
+                        (PathLinks::Unchecked, steps)
+                    }
+                    _ => (PathLinks::Strict, rest),
+                };
+                EO::AddPath {
+                    name: BString::from(*name),
+                    steps: steps(rest),
+                    links,
+                }
+            }
+            (["REMOVE", "NODE"], [id]) => EO::RemoveNode {
+                id: BString::from(*id),
+            },
+            (["REMOVE", "LINK"], [from, to]) => EO::RemoveLink {
+                from: oriented(from)?,
+                to: oriented(to)?,
+            },
+            (["REMOVE", "PATH"], [name]) => EO::RemovePath {
+                name: BString::from(*name),
+            },
+            (["MODIFY", "NODE"], [id, sequence]) => EO::ModifyNode {
+                id: BString::from(*id),
+                sequence: BString::from(*sequence),
+            },
+            (["MODIFY", "LINK"], [from, to, new_from, new_to, overlap @ ..])
+                if overlap.len() <= 1 =>
+            {
+                EO::ModifyLink {
+                    from: oriented(from)?,
+                    to: oriented(to)?,
+                    new_from: value(new_from).map(|_| oriented(new_from)).transpose()?,
+                    new_to: value(new_to).map(|_| oriented(new_to)).transpose()?,
+                    overlap: overlap.first().map(|overlap| BString::from(*overlap)),
+                }
+            }
+            (["MODIFY", "PATH"], [name, rest @ ..]) => EO::ModifyPath {
+                name: BString::from(*name),
+                steps: steps(rest),
+            },
+            (["SET", "TAG"], rest) => match parse_target(rest) {
+                Some((target, [field])) => EO::SetTag {
+                    target,
+                    field: BString::from(*field),
+                },
+                _ => return Err(invalid()),
+            },
+            (["REMOVE", "TAG"], rest) => match parse_target(rest) {
+                Some((target, [tag])) => EO::RemoveTag {
+                    target,
+                    tag: BString::from(*tag),
+                },
+                _ => return Err(invalid()),
+            },
+            (["ADD", "CONTAINMENT"], [container, contained, pos, overlap, tags @ ..]) => {
+                EO::AddContainment {
+                    containment: Containment {
+                        container: oriented(container)?,
+                        contained: oriented(contained)?,
+                        pos: pos.parse().map_err(|_| invalid())?,
+                        overlap: BString::from(*overlap),
+                        tags: parse_tags(tags)?,
+                    },
+                }
+            }
+            (["REMOVE", "CONTAINMENT"], [container, contained]) => EO::RemoveContainment {
+                container: oriented(container)?,
+                contained: oriented(contained)?,
+            },
+            (["SET", "WALK"], [name, sample, haplotype, seq_id, start, end]) => EO::SetWalk {
+                name: BString::from(*name),
+                walk: Walk::new(
+                    sample.as_bytes(),
+                    haplotype.as_bytes(),
+                    seq_id.as_bytes(),
+                    start.as_bytes(),
+                    end.as_bytes(),
+                )
+                .map_err(|_| invalid())?,
+            },
+            (["REMOVE", "WALK"], [name]) => EO::RemoveWalk {
+                name: BString::from(*name),
+            },
+            (
+                ["ADD", "FRAGMENT"],
+                [segment, external, sbeg, send, fbeg, fend, alignment, tags @ ..],
+            ) => EO::AddFragment {
+                fragment: Fragment {
+                    segment: BString::from(*segment),
+                    external: oriented(external)?,
+                    sbeg: BString::from(*sbeg),
+                    send: BString::from(*send),
+                    fbeg: BString::from(*fbeg),
+                    fend: BString::from(*fend),
+                    alignment: BString::from(*alignment),
+                    tags: parse_tags(tags)?,
+                },
+            },
+            (["REMOVE", "FRAGMENT"], [segment, external]) => EO::RemoveFragment {
+                segment: BString::from(*segment),
+                external: oriented(external)?,
+                positions: None,
+            },
+            (["REMOVE", "FRAGMENT"], [segment, external, sbeg, send, fbeg, fend]) => {
+                EO::RemoveFragment {
+                    segment: BString::from(*segment),
+                    external: oriented(external)?,
+                    positions: Some([
+                        BString::from(*sbeg),
+                        BString::from(*send),
+                        BString::from(*fbeg),
+                        BString::from(*fend),
+                    ]),
+                }
+            }
+            (["ADD", "GAP"], [id, from, to, distance, variance, tags @ ..]) => EO::AddGap {
+                gap: Gap {
+                    id: BString::from(*id),
+                    from: oriented(from)?,
+                    to: oriented(to)?,
+                    distance: BString::from(*distance),
+                    variance: BString::from(*variance),
+                    tags: parse_tags(tags)?,
+                },
+            },
+            (["REMOVE", "GAP"], [id]) => EO::RemoveGap {
+                gap: GapReference::Id(BString::from(*id)),
+            },
+            (["REMOVE", "GAP"], [from, to]) => EO::RemoveGap {
+                gap: GapReference::Ends(oriented(from)?, oriented(to)?),
+            },
+            (["ADD", "GROUP"], [id, rest @ ..]) => {
+                // the members are followed by the tags
+                let (tags, members): (Vec<&str>, Vec<&str>) = rest
+                    .iter()
+                    .partition(|field| parse_tag(field.as_bytes()).is_ok());
+                if members.is_empty() {
+                    return Err(invalid());
+                }
+                EO::AddGroup {
+                    group: UnorderedGroup {
+                        id: BString::from(*id),
+                        members: steps(&members),
+                        tags: parse_tags(&tags)?,
+                    },
+                }
+            }
+            (["REMOVE", "GROUP"], [id]) => EO::RemoveGroup {
+                id: BString::from(*id),
+            },
+            (["MODIFY", "GROUP"], [id, rest @ ..]) if !rest.is_empty() => EO::ModifyGroup {
+                id: BString::from(*id),
+                members: steps(rest),
+            },
+            _ => return Err(invalid()),
+        };
+        Ok(edit)
+    }
+}
+
+impl GFAGraph {
+    /// Apply an ```EditOperation``` and return the operations that undo it.\
+    /// If an error is returned the graph is left unchanged
+    /// # Example
+    /// ```ignore
+    /// use handle_gfa::graphoperation::*;
+    ///
+    /// let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+    /// let undo = graph.apply(&EditOperation::RemoveNode { id: "11".into() }).unwrap();
+    /// for operation in undo.iter() {
+    ///     graph.apply(operation).unwrap();
+    /// }
+    /// ```
+    pub fn apply(
+        &mut self,
+        operation: &EditOperation,
+    ) -> Result<Vec<EditOperation>, GraphOperationError> {
+        use EditOperation as EO;
+
+        let inverse = self.inverse(operation)?;
+        match operation {
+            EO::AddNode {
+                id,
+                sequence,
+                length,
+            } => {
+                self.add_node(id, sequence.as_ref().map(|seq| seq.as_bytes()))?;
+                if let Some(length) = length {
+                    let node = self.node_id(id)?;
+                    self.segment_lengths.insert(node, *length);
+                }
+            }
+            EO::AddLink { from, to, overlap } => self.add_link_between_nodes(
+                from,
+                to,
+                overlap.as_ref().map(|overlap| overlap.as_bytes()),
+            )?,
+            EO::AddEdge {
+                id,
+                from,
+                to,
+                overlap,
+            } => self.add_edge(id, from, to, overlap.clone())?,
+            EO::AddPath { name, steps, links } => {
+                self.add_path_with_links(name, as_slices(steps), *links)?
+            }
+            EO::RemoveNode { id } => self.remove_node(id)?,
+            EO::RemoveLink { from, to } => self.remove_link(from, to)?,
+            EO::RemovePath { name } => self.remove_path(Some(name))?,
+            EO::ModifyNode { id, sequence } => self.modify_node(id, sequence)?,
+            EO::ModifyLink {
+                from,
+                to,
+                new_from,
+                new_to,
+                overlap,
+            } => self.modify_link(
+                from,
+                to,
+                new_from.as_ref().map(|id| id.as_bytes()),
+                new_to.as_ref().map(|id| id.as_bytes()),
+                overlap.as_ref().map(|overlap| overlap.as_bytes()),
+            )?,
+            EO::ModifyPath { name, steps } => self.modify_path(name, as_slices(steps))?,
+            EO::SetTag { target, field } => match target {
+                TagTarget::Node(id) => self.set_node_tag(id, field)?,
+                TagTarget::Link(from, to) => self.set_link_tag(from, to, field)?,
+                TagTarget::Path(name) => self.set_path_tag(name, field)?,
+            },
+            EO::RemoveTag { target, tag } => match target {
+                TagTarget::Node(id) => self.remove_node_tag(id, tag)?,
+                TagTarget::Link(from, to) => self.remove_link_tag(from, to, tag)?,
+                TagTarget::Path(name) => self.remove_path_tag(name, tag)?,
+            },
+            EO::AddContainment { containment } => self.add_containment(containment.clone())?,
+            EO::RemoveContainment {
+                container,
+                contained,
+            } => self.remove_containment(container, contained)?,
+            EO::SetWalk { name, walk } => self.set_walk(name, walk.clone())?,
+            EO::RemoveWalk { name } => self.remove_walk(name)?,
+            EO::AddFragment { fragment } => self.add_fragment(fragment.clone())?,
+            EO::RemoveFragment {
+                segment,
+                external,
+                positions,
+            } => self.remove_fragment(segment, external, positions.as_ref())?,
+            EO::AddGap { gap } => self.add_gap(gap.clone())?,
+            EO::RemoveGap { gap } => self.remove_gap(gap)?,
+            EO::AddGroup { group } => self.add_group(group.clone())?,
+            EO::RemoveGroup { id } => self.remove_group(id)?,
+            EO::ModifyGroup { id, members } => self.modify_group(id, as_slices(members))?,
+        }
+        Ok(inverse)
+    }
+
+    // compute the operations that restore the current state of the graph
+    // after the operation has been applied
+    fn inverse(
+        &self,
+        operation: &EditOperation,
+    ) -> Result<Vec<EditOperation>, GraphOperationError> {
+        use EditOperation as EO;
+
+        let inverse = match operation {
+            EO::AddNode { id, .. } => vec![EO::RemoveNode { id: id.clone() }],
+            EO::AddLink { from, to, .. } | EO::AddEdge { from, to, .. } => vec![EO::RemoveLink {
+                from: from.clone(),
+                to: to.clone(),
+            }],
+            EO::AddPath { name, steps, links } => {
+                let mut inverse = vec![EO::RemovePath { name: name.clone() }];
+                // the links created with the path are removed after it
+                if *links == PathLinks::Create {
+                    for (from, to) in self.missing_path_links(&as_slices(steps))? {
+                        inverse.push(EO::RemoveLink { from, to });
+                    }
+                }
+                inverse
+            }
+            EO::RemoveNode { id } => self.restore_node(id)?,
+            EO::RemoveLink { from, to } => {
+                let edge = self.existing_edge(from, to)?;
+                self.restore_link(edge)
+            }
+            EO::RemovePath { name } => self.restore_path(name)?,
+            EO::ModifyNode { id, .. } => {
+                let node = self.node_id(id)?;
+                vec![EO::ModifyNode {
+                    id: id.clone(),
+                    sequence: BString::from(
+                        self.graph.sequence(Handle::new(node, Orientation::Forward)),
+                    ),
+                }]
+            }
+            EO::ModifyLink {
+                from,
+                to,
+                new_from,
+                new_to,
+                overlap,
+            } => {
+                let edge = self.existing_edge(from, to)?;
+                let new_from = new_from.clone().unwrap_or_else(|| from.clone());
+                let new_to = new_to.clone().unwrap_or_else(|| to.clone());
+                // the positions of an edge read from a GFA2 file are dropped by the change,
+                // so the edge is added back as it was
+                if let Some(Overlap {
+                    positions: Some(_), ..
+                }) = self.edge_overlap(edge)
+                {
+                    let mut inverse = vec![EO::RemoveLink {
+                        from: new_from,
+                        to: new_to,
+                    }];
+                    inverse.extend(self.restore_link(edge));
+                    return Ok(inverse);
+                }
+                // a new overlap is taken back setting the old one
+                let old_overlap = overlap.as_ref().map(|_| self.link_overlap(edge));
+                vec![EO::ModifyLink {
+                    from: new_from,
+                    to: new_to,
+                    new_from: Some(from.clone()),
+                    new_to: Some(to.clone()),
+                    overlap: old_overlap,
+                }]
+            }
+            EO::ModifyPath { name, .. } => vec![EO::ModifyPath {
+                name: name.clone(),
+                steps: self.path_steps(name)?,
+            }],
+            EO::SetTag { target, field } => {
+                let tag = BString::from(field.get(..2).unwrap_or(&[]));
+                match self
+                    .target_tags(target)?
+                    .and_then(|tags| find_field(tags, &tag))
+                {
+                    Some(old) => vec![EO::SetTag {
+                        target: target.clone(),
+                        field: old,
+                    }],
+                    None => vec![EO::RemoveTag {
+                        target: target.clone(),
+                        tag,
+                    }],
+                }
+            }
+            EO::RemoveTag { target, tag } => {
+                match self
+                    .target_tags(target)?
+                    .and_then(|tags| find_field(tags, tag))
+                {
+                    Some(old) => vec![EO::SetTag {
+                        target: target.clone(),
+                        field: old,
+                    }],
+                    // the operation will fail, so there is nothing to undo
+                    None => vec![],
+                }
+            }
+            EO::AddContainment { containment } => vec![EO::RemoveContainment {
+                container: containment.container.clone(),
+                contained: containment.contained.clone(),
+            }],
+            EO::RemoveContainment {
+                container,
+                contained,
+            } => self
+                .containment(container, contained)
+                .map(|containment| EO::AddContainment {
+                    containment: containment.clone(),
+                })
+                .into_iter()
+                .collect(),
+            EO::SetWalk { name, .. } => match self.walk(name) {
+                Some(walk) => vec![EO::SetWalk {
+                    name: name.clone(),
+                    walk: walk.clone(),
+                }],
+                None => vec![EO::RemoveWalk { name: name.clone() }],
+            },
+            EO::RemoveWalk { name } => self
+                .walk(name)
+                .map(|walk| EO::SetWalk {
+                    name: name.clone(),
+                    walk: walk.clone(),
+                })
+                .into_iter()
+                .collect(),
+            EO::AddFragment { fragment } => vec![EO::RemoveFragment {
+                segment: fragment.segment.clone(),
+                external: fragment.external.clone(),
+                positions: Some(fragment.positions()),
+            }],
+            EO::RemoveFragment {
+                segment,
+                external,
+                positions,
+            } => self
+                .fragment(segment, external, positions.as_ref())
+                .map(|fragment| EO::AddFragment {
+                    fragment: fragment.clone(),
+                })
+                .into_iter()
+                .collect(),
+            EO::AddGap { gap } => vec![EO::RemoveGap {
+                gap: gap.reference(),
+            }],
+            EO::RemoveGap { gap } => self
+                .gap(gap)
+                .map(|gap| EO::AddGap { gap: gap.clone() })
+                .into_iter()
+                .collect(),
+            EO::AddGroup { group } => vec![EO::RemoveGroup {
+                id: group.id.clone(),
+            }],
+            EO::RemoveGroup { id } => self
+                .group(id)
+                .map(|group| EO::AddGroup {
+                    group: group.clone(),
+                })
+                .into_iter()
+                .collect(),
+            EO::ModifyGroup { id, .. } => self
+                .group(id)
+                .map(|group| EO::ModifyGroup {
+                    id: id.clone(),
+                    members: group.members.clone(),
+                })
+                .into_iter()
+                .collect(),
+        };
+        Ok(inverse)
+    }
+
+    fn path_steps(&self, name: &[u8]) -> Result<Vec<BString>, GraphOperationError> {
+        match self.graph.path_id.get(name) {
+            Some(path_id) => Ok(self.graph.paths[path_id]
+                .nodes
+                .iter()
+                .map(|step| BString::from(self.oriented_id(*step).to_string()))
+                .collect()),
+            None => Err(GraphOperationError::PathNotExist(
+                name.to_str_lossy().to_string(),
+            )),
+        }
+    }
+
+    fn target_tags(
+        &self,
+        target: &TagTarget,
+    ) -> Result<Option<&gfa2::tag::OptionalFields>, GraphOperationError> {
+        match target {
+            TagTarget::Node(id) => Ok(self.get_segment_tags(self.node_id(id)?)),
+            TagTarget::Link(from, to) => Ok(self.get_edge_tags(self.existing_edge(from, to)?)),
+            TagTarget::Path(name) => {
+                if self.has_path(name) {
+                    Ok(self.get_path_tags(name))
+                } else {
+                    Err(GraphOperationError::PathNotExist(
+                        name.to_str_lossy().to_string(),
+                    ))
+                }
+            }
+        }
+    }
+
+    // the CIGAR of the overlap of a link, 0M for a blunt one
+    fn link_overlap(&self, edge: Edge) -> BString {
+        match self.edge_overlap(edge) {
+            Some(overlap) => overlap.gfa1_overlap(),
+            None => BString::from("0M"),
+        }
+    }
+
+    // the link with its overlap (the positions of an edge read from a GFA2 file too) and its tags
+    fn restore_link(&self, edge: Edge) -> Vec<EditOperation> {
+        let Edge(left, right) = edge;
+        let from = BString::from(self.oriented_id(left).to_string());
+        let to = BString::from(self.oriented_id(right).to_string());
+
+        let id = self.edge_id(edge);
+        let mut operations = vec![match self.edge_overlap(edge) {
+            Some(overlap) if overlap.positions.is_some() => EditOperation::AddEdge {
+                id,
+                from: from.clone(),
+                to: to.clone(),
+                overlap,
+            },
+            // an edge with an id is written with the positions it would be saved with
+            _ if id != "*" => {
+                let (positions, alignment) = self.gfa2_edge_positions(edge);
+                EditOperation::AddEdge {
+                    id,
+                    from: from.clone(),
+                    to: to.clone(),
+                    overlap: Overlap {
+                        alignment,
+                        positions: Some(positions.map(BString::from)),
+                    },
+                }
+            }
+            overlap => EditOperation::AddLink {
+                from: from.clone(),
+                to: to.clone(),
+                overlap: overlap.map(|_| self.link_overlap(edge)),
+            },
+        }];
+        operations.extend(set_tags(
+            TagTarget::Link(from, to),
+            self.get_edge_tags(edge),
+        ));
+        operations
+    }
+
+    // the path, with its tags and its haplotype
+    fn restore_path(&self, name: &[u8]) -> Result<Vec<EditOperation>, GraphOperationError> {
+        // the path is restored as it was, even if its steps were not joined by links
+        let mut operations = vec![EditOperation::AddPath {
+            name: BString::from(name),
+            steps: self.path_steps(name)?,
+            links: PathLinks::Unchecked,
+        }];
+        operations.extend(set_tags(
+            TagTarget::Path(BString::from(name)),
+            self.get_path_tags(name),
+        ));
+        if let Some(walk) = self.walk(name) {
+            operations.push(EditOperation::SetWalk {
+                name: BString::from(name),
+                walk: walk.clone(),
+            });
+        }
+        Ok(operations)
+    }
+
+    // the node with its sequence and tags, every link that touches it,
+    // the steps of the paths that go through it and its containments, fragments, gaps and groups
+    fn restore_node(&self, id: &[u8]) -> Result<Vec<EditOperation>, GraphOperationError> {
+        let node = self.node_id(id)?;
+        let handle = Handle::new(node, Orientation::Forward);
+
+        let mut operations = vec![EditOperation::AddNode {
+            id: BString::from(id),
+            sequence: Some(BString::from(self.graph.sequence(handle))),
+            length: self.segment_lengths.get(&node).copied(),
+        }];
+        operations.extend(set_tags(
+            TagTarget::Node(BString::from(id)),
+            self.get_segment_tags(node),
+        ));
+
+        let mut edges = HashSet::new();
+        for next in self.graph.handle_edges_iter(handle, Direction::Right) {
+            edges.insert(edge_key(Edge(handle, next)));
+        }
+        for prev in self.graph.handle_edges_iter(handle, Direction::Left) {
+            edges.insert(edge_key(Edge(prev, handle)));
+        }
+        let mut edges: Vec<Edge> = edges.into_iter().collect();
+        edges.sort();
+        for edge in edges {
+            operations.extend(self.restore_link(edge));
+        }
+
+        let mut paths: Vec<&[u8]> = self
+            .graph
+            .paths
+            .values()
+            .filter(|path| path.nodes.iter().any(|step| step.id() == node))
+            .map(|path| path.name.as_slice())
+            .collect();
+        paths.sort();
+        for name in paths {
+            operations.push(EditOperation::ModifyPath {
+                name: BString::from(name),
+                steps: self.path_steps(name)?,
+            });
+        }
+
+        for containment in self
+            .containments
+            .iter()
+            .filter(|containment| containment.touches(id))
+        {
+            operations.push(EditOperation::AddContainment {
+                containment: containment.clone(),
+            });
+        }
+        for fragment in self
+            .fragments
+            .iter()
+            .filter(|fragment| fragment.segment == id)
+        {
+            operations.push(EditOperation::AddFragment {
+                fragment: fragment.clone(),
+            });
+        }
+        for gap in self.gaps.iter().filter(|gap| gap.touches(id)) {
+            operations.push(EditOperation::AddGap { gap: gap.clone() });
+        }
+        for group in self.groups.iter() {
+            if !group.members.iter().any(|member| member == id) {
+                continue;
+            }
+            // a group left without members is removed with the node
+            if group.members.iter().all(|member| member == id) {
+                operations.push(EditOperation::AddGroup {
+                    group: group.clone(),
+                });
+            } else {
+                operations.push(EditOperation::ModifyGroup {
+                    id: group.id.clone(),
+                    members: group.members.clone(),
+                });
+            }
+        }
+        Ok(operations)
+    }
+}
+
+/// How the steps of a path are checked against the links of the graph when it's added
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathLinks {
+    /// every 2 consecutive steps must be joined by a link, otherwise the path is rejected
+    #[default]
+    Strict,
+    /// the links missing between consecutive steps are created (blunt) together with the path
+    Create,
+    /// the consecutive steps do not need to be joined by a link
+    Unchecked,
+}
+
+// the keyword that follows the steps of ADD PATH, nothing for the default
+impl fmt::Display for PathLinks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathLinks::Strict => Ok(()),
+            PathLinks::Create => write!(f, " CREATE-LINKS"),
+            PathLinks::Unchecked => write!(f, " UNCHECKED"),
+        }
+    }
+}
+
+/// The links created together with a path are part of its ```ADD PATH``` operation,
+/// so they are undone and recorded in a journal with the path
+impl GFAGraph {
+    /// Return the links missing between the consecutive steps of a path,
+    /// every one only once, checking that every segment exists
+    pub fn missing_path_links(
+        &self,
+        steps: &[&[u8]],
+    ) -> Result<Vec<(BString, BString)>, GraphOperationError> {
+        let handles = steps
+            .iter()
+            .map(|step| self.oriented_handle(step))
+            .collect::<Result<Vec<Handle>, GraphOperationError>>()?;
+        let mut missing = vec![];
+        let mut found = HashSet::new();
+        for (i, pair) in handles.windows(2).enumerate() {
+            let edge = Edge(pair[0], pair[1]);
+            if !self.has_edge(edge) && found.insert(edge_key(edge)) {
+                missing.push((BString::from(steps[i]), BString::from(steps[i + 1])));
+            }
+        }
+        Ok(missing)
+    }
+}
+
+fn as_slices(steps: &[BString]) -> Vec<&[u8]> {
+    steps.iter().map(|step| step.as_bytes()).collect()
+}
+
+fn find_field(tags: &gfa2::tag::OptionalFields, tag: &[u8]) -> Option<BString> {
+    tags.iter()
+        .find(|field| &field.tag[..] == tag)
+        .map(|field| BString::from(field.to_string()))
+}
+
+fn set_tags(target: TagTarget, tags: Option<&gfa2::tag::OptionalFields>) -> Vec<EditOperation> {
+    tags.map(|tags| {
+        tags.iter()
+            .map(|field| EditOperation::SetTag {
+                target: target.clone(),
+                field: BString::from(field.to_string()),
+            })
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn can_check_the_links_of_a_path() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        match graph.add_link_between_nodes(b"41+", b"42+", None) {
+            Err(GraphOperationError::NodesNotExist(left, right)) => {
+                assert_eq!((left.as_str(), right.as_str()), ("41", "42"))
+            }
+            _ => panic!("the nodes do not exist"),
+        }
+
+        graph.add_node(b"16", Some(b"GATTACA")).unwrap();
+        let steps: Vec<&[u8]> = vec![b"11+", b"16+", b"13-", b"16+"];
+        assert_eq!(graph.missing_path_links(&steps).unwrap().len(), 3);
+        let operation: EditOperation = "ADD PATH 17 11+ 16+ 13- 16+".parse().unwrap();
+        match graph.apply(&operation) {
+            Err(GraphOperationError::EdgeNotExist(from, to)) => {
+                assert_eq!((from.as_str(), to.as_str()), ("11+", "16+"))
+            }
+            _ => panic!("the path is not joined by links"),
+        }
+        assert!(!graph.has_path(b"17"));
+        assert!(graph.add_path(Some(b"17"), steps.clone()).is_err());
+
+        let mut history = History::new();
+        let operation: EditOperation = "add path 17 11+ 16+ 13- 16+ create-links".parse().unwrap();
+        assert_eq!(
+            operation.to_string(),
+            "ADD PATH 17 11+ 16+ 13- 16+ CREATE-LINKS"
+        );
+        history.apply(&mut graph, operation).unwrap();
+        assert!(graph.missing_path_links(&steps).unwrap().is_empty());
+        graph.add_path(Some(b"18"), vec![b"16-", b"11-"]).unwrap();
+        graph.remove_path(Some(b"18")).unwrap();
+        history.undo(&mut graph).unwrap();
+        assert!(!graph.has_path(b"17"));
+        assert!(graph.existing_edge(b"11+", b"16+").is_err());
+        assert_eq!(graph.missing_path_links(&steps).unwrap().len(), 3);
+
+        // a path removed is restored as it was
+        graph
+            .add_path_with_links(b"17", steps.clone(), PathLinks::Unchecked)
+            .unwrap();
+        let undo = graph.apply(&"REMOVE PATH 17".parse().unwrap()).unwrap();
+        assert_eq!(undo[0].to_string(), "ADD PATH 17 11+ 16+ 13- 16+ UNCHECKED");
+        graph.apply(&undo[0]).unwrap();
+        assert!(graph.has_path(b"17"));
+    }
+}
@@ -0,0 +1,115 @@
+/// parse the references to the segments with their orientation (```11+```, ```utg1-```)
+use bstr::{BString, ByteSlice};
+use gfa2::gfa2::orientation::Orientation;
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+use super::error::*;
+
+/// A reference to a segment together with its orientation,
+/// written as the name of the segment followed by ```+``` or ```-```
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let id: OrientedId = "utg000123l-".parse().unwrap();
+/// assert_eq!(id.name, "utg000123l");
+/// assert!(id.is_reverse());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OrientedId {
+    pub name: BString,
+    pub orientation: Orientation,
+}
+
+// a segment name is made of printable characters and cannot start with "*" or "="
+fn is_valid_name(name: &[u8]) -> bool {
+    !name.is_empty()
+        && name[0] != b'*'
+        && name[0] != b'='
+        && name.iter().all(|c| (b'!'..=b'~').contains(c))
+}
+
+impl OrientedId {
+    pub fn new(name: &[u8], orientation: Orientation) -> Self {
+        OrientedId {
+            name: BString::from(name),
+            orientation,
+        }
+    }
+
+    pub fn is_reverse(&self) -> bool {
+        self.orientation == Orientation::Backward
+    }
+}
+
+impl fmt::Display for OrientedId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.name, self.orientation)
+    }
+}
+
+impl TryFrom<&[u8]> for OrientedId {
+    type Error = GraphOperationError;
+
+    fn try_from(reference: &[u8]) -> Result<Self, Self::Error> {
+        let (last, name) = match reference.split_last() {
+            Some(split) => split,
+            None => return Err(GraphOperationError::EmptyId),
+        };
+        let orientation = match last {
+            b'+' => Orientation::Forward,
+            b'-' => Orientation::Backward,
+            _ => {
+                return Err(GraphOperationError::InvalidOrientedId(
+                    reference.to_str_lossy().to_string(),
+                ))
+            }
+        };
+        if !is_valid_name(name) {
+            return Err(GraphOperationError::InvalidId(
+                reference.to_str_lossy().to_string(),
+            ));
+        }
+        Ok(OrientedId::new(name, orientation))
+    }
+}
+
+impl FromStr for OrientedId {
+    type Err = GraphOperationError;
+
+    fn from_str(reference: &str) -> Result<Self, Self::Err> {
+        OrientedId::try_from(reference.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_oriented_id() {
+        let id: OrientedId = "utg000123l-".parse().unwrap();
+        assert_eq!(id.name, "utg000123l");
+        assert!(id.is_reverse());
+        assert_eq!(id.to_string(), "utg000123l-");
+        assert_eq!(OrientedId::try_from(&b"11+"[..]).unwrap().name, "11");
+    }
+
+    #[test]
+    fn cannot_parse_wrong_oriented_id() {
+        match "".parse::<OrientedId>() {
+            Err(GraphOperationError::EmptyId) => (),
+            _ => panic!("an empty reference should be rejected"),
+        }
+        match "11".parse::<OrientedId>() {
+            Err(GraphOperationError::InvalidOrientedId(_)) => (),
+            _ => panic!("a reference without orientation should be rejected"),
+        }
+        for id in ["+", "*+", "utg 1+", "\u{e8}+"].iter() {
+            match id.parse::<OrientedId>() {
+                Err(GraphOperationError::InvalidId(_)) => (),
+                _ => panic!("{} should be rejected", id),
+            }
+        }
+    }
+}
@@ -0,0 +1,395 @@
+/// keep the overlap of the links (GFA1) and the positions and alignment of the edges (GFA2)
+use bstr::{BString, ByteSlice};
+use handlegraph2::{
+    handle::{Edge, Handle, NodeId},
+    handlegraph::HandleGraph,
+};
+
+use super::error::*;
+use super::gfagraph::*;
+use super::tag::*;
+
+/// The overlap between the ends of a link
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overlap {
+    /// the CIGAR of the overlap (```5M```), a trace for the edges read from a ```GFA2``` file,
+    /// or ```*``` when not known
+    pub alignment: BString,
+    /// the positions (```beg1 end1 beg2 end2```) of the ```GFA2``` edge it has been read from.
+    /// Without them the positions are computed from the CIGAR, as the link is a dovetail
+    pub positions: Option<[BString; 4]>,
+}
+
+/// Read the operations of a CIGAR string (```3M1I2M```), ```None``` if it's not one
+pub fn cigar_operations(cigar: &[u8]) -> Option<Vec<(usize, u8)>> {
+    let mut operations = vec![];
+    let mut length = String::new();
+    for c in cigar.iter() {
+        match c {
+            b'0'..=b'9' => length.push(*c as char),
+            b'M' | b'I' | b'D' | b'N' | b'S' | b'H' | b'P' | b'X' | b'=' if !length.is_empty() => {
+                operations.push((length.parse().ok()?, *c));
+                length.clear();
+            }
+            _ => return None,
+        }
+    }
+    if operations.is_empty() || !length.is_empty() {
+        None
+    } else {
+        Some(operations)
+    }
+}
+
+// a position of a GFA2 edge, with the $ that marks the end of the segment
+fn position(value: usize, length: usize) -> String {
+    if value == length {
+        format!("{}$", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn position_value(position: &[u8]) -> Option<usize> {
+    position
+        .trim_end_with(|c| c == '$')
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+impl Overlap {
+    /// An overlap given as a CIGAR string (or ```*``` when not known)
+    pub fn new(alignment: &[u8]) -> Result<Self, GraphOperationError> {
+        if alignment != b"*" && cigar_operations(alignment).is_none() {
+            return Err(GraphOperationError::InvalidOverlap(
+                alignment.to_str_lossy().to_string(),
+            ));
+        }
+        Ok(Overlap {
+            alignment: BString::from(alignment),
+            positions: None,
+        })
+    }
+
+    /// The overlap of an edge read from a ```GFA2``` file, with its positions
+    /// (the alignment can be a trace, or ```*```)
+    pub fn with_positions(
+        alignment: &[u8],
+        positions: [BString; 4],
+    ) -> Result<Self, GraphOperationError> {
+        if let Some(position) = positions
+            .iter()
+            .find(|position| position_value(position).is_none())
+        {
+            return Err(GraphOperationError::InvalidOverlap(position.to_string()));
+        }
+        Ok(Overlap {
+            alignment: BString::from(alignment),
+            positions: Some(positions),
+        })
+    }
+
+    /// The overlap of a blunt link, that's never stored
+    pub fn is_blunt(&self) -> bool {
+        self.alignment == "0M" && self.positions.is_none()
+    }
+
+    /// The length of the overlap on the first and on the second segment of the link,
+    /// told by the CIGAR or by the positions of the edge
+    pub fn lengths(&self) -> Option<(usize, usize)> {
+        if let Some(operations) = cigar_operations(&self.alignment) {
+            let length = |consumes: &[u8]| {
+                operations
+                    .iter()
+                    .filter(|(_, operation)| consumes.contains(operation))
+                    .map(|(length, _)| length)
+                    .sum()
+            };
+            return Some((length(b"MDN=X"), length(b"MIS=X")));
+        }
+        match &self.positions {
+            Some([beg1, end1, beg2, end2]) => Some((
+                position_value(end1)?.checked_sub(position_value(beg1)?)?,
+                position_value(end2)?.checked_sub(position_value(beg2)?)?,
+            )),
+            None => None,
+        }
+    }
+
+    /// The same overlap read from the other end of the link (```12- -> 11-``` for ```11+ -> 12+```)
+    pub fn reversed(&self) -> Self {
+        let alignment = match cigar_operations(&self.alignment) {
+            Some(operations) => {
+                let cigar: String = operations
+                    .iter()
+                    .rev()
+                    .map(|(length, operation)| {
+                        let operation = match operation {
+                            b'I' => 'D',
+                            b'D' => 'I',
+                            _ => *operation as char,
+                        };
+                        format!("{}{}", length, operation)
+                    })
+                    .collect();
+                BString::from(cigar)
+            }
+            None => self.alignment.clone(),
+        };
+        Overlap {
+            alignment,
+            positions: self.positions.as_ref().map(|[beg1, end1, beg2, end2]| {
+                [beg2.clone(), end2.clone(), beg1.clone(), end1.clone()]
+            }),
+        }
+    }
+
+    /// The overlap written in the ```L``` record of a ```GFA1``` file
+    pub fn gfa1_overlap(&self) -> BString {
+        if self.alignment == "*" || cigar_operations(&self.alignment).is_some() {
+            return self.alignment.clone();
+        }
+        match self.lengths() {
+            Some((first, second)) if first == second => BString::from(format!("{}M", first)),
+            _ => BString::from("*"),
+        }
+    }
+}
+
+/// The overlap of a link is kept together with the link, like its tags
+impl GFAGraph {
+    /// Return the overlap of a link, read from its first end to its second one
+    pub fn edge_overlap(&self, edge: Edge) -> Option<Overlap> {
+        let key = edge_key(edge);
+        let overlap = self.edge_overlaps.get(&key)?;
+        if key == edge {
+            Some(overlap.clone())
+        } else {
+            Some(overlap.reversed())
+        }
+    }
+
+    /// Set (or remove, with ```None```) the overlap of a link, read from its first end to its second one
+    pub fn set_edge_overlap(&mut self, edge: Edge, overlap: Option<Overlap>) {
+        let key = edge_key(edge);
+        match overlap {
+            Some(overlap) if !overlap.is_blunt() => {
+                let overlap = if key == edge {
+                    overlap
+                } else {
+                    overlap.reversed()
+                };
+                self.edge_overlaps.insert(key, overlap);
+            }
+            _ => {
+                self.edge_overlaps.remove(&key);
+            }
+        }
+    }
+
+    /// The length of a segment, given by its sequence or, for the segments without one (```*```),
+    /// by the length read from a ```GFA2``` file or by the ```LN``` tag
+    pub fn segment_length(&self, id: NodeId) -> usize {
+        let sequence = self.graph.sequence(Handle::pack(id, false));
+        if sequence != b"*" {
+            return sequence.len();
+        }
+        if let Some(length) = self.segment_lengths.get(&id) {
+            return *length;
+        }
+        match find_tag(self.get_segment_tags(id), b"LN") {
+            Some(field) => field
+                .to_string()
+                .get(5..)
+                .and_then(|length| length.parse().ok())
+                .unwrap_or(0),
+            None => 0,
+        }
+    }
+
+    /// The positions and the alignment written in the ```E``` record of a ```GFA2``` file.
+    /// When they have not been read from a ```GFA2``` file the link is a dovetail overlap:
+    /// the end of the first segment (the start when reversed) over the start of the second one
+    pub fn gfa2_edge_positions(&self, edge: Edge) -> ([String; 4], BString) {
+        let overlap = self.edge_overlap(edge);
+        if let Some(Overlap {
+            alignment,
+            positions: Some(positions),
+        }) = &overlap
+        {
+            return (
+                [
+                    positions[0].to_string(),
+                    positions[1].to_string(),
+                    positions[2].to_string(),
+                    positions[3].to_string(),
+                ],
+                alignment.clone(),
+            );
+        }
+        let (first, second) = overlap
+            .as_ref()
+            .and_then(|overlap| overlap.lengths())
+            .unwrap_or((0, 0));
+        let alignment = overlap
+            .map(|overlap| overlap.alignment)
+            .unwrap_or_else(|| BString::from("0M"));
+
+        let Edge(left, right) = edge;
+        let left_length = self.segment_length(left.id());
+        let right_length = self.segment_length(right.id());
+        let first = first.min(left_length);
+        let second = second.min(right_length);
+        let (beg1, end1) = if left.is_reverse() {
+            (0, first)
+        } else {
+            (left_length - first, left_length)
+        };
+        let (beg2, end2) = if right.is_reverse() {
+            (right_length - second, right_length)
+        } else {
+            (0, second)
+        };
+        (
+            [
+                position(beg1, left_length),
+                position(end1, left_length),
+                position(beg2, right_length),
+                position(end2, right_length),
+            ],
+            alignment,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_read_overlaps() {
+        let overlap = Overlap::new(b"3M1I2M").unwrap();
+        assert_eq!(overlap.lengths(), Some((5, 6)));
+        assert_eq!(overlap.reversed().alignment, "2M1D3M");
+        assert!(Overlap::new(b"3Q").is_err());
+        assert!(Overlap::new(b"M").is_err());
+
+        let overlap = Overlap {
+            alignment: BString::from("1,2"),
+            positions: Some(["2".into(), "7$".into(), "0".into(), "5".into()]),
+        };
+        assert_eq!(overlap.gfa1_overlap(), "5M");
+        assert_eq!(overlap.reversed().positions.unwrap()[0], BString::from("0"));
+    }
+
+    #[test]
+    fn can_save_the_overlaps() {
+        use super::super::*;
+        use crate::fileoperation::*;
+
+        let gfa1 = b"H\tVN:Z:1.0\nS\t11\tACCTT\nS\t12\tTCAAGG\nS\t13\t*\tLN:i:8\n\
+            L\t11\t+\t12\t-\t2M\nL\t12\t-\t13\t+\t*\n";
+        let (_, mut graph) = load_graph_from_reader(&gfa1[..]).unwrap();
+        graph
+            .add_link_between_nodes(b"13-", b"11-", Some(b"1M1I1M"))
+            .unwrap();
+        assert!(graph
+            .add_link_between_nodes(b"11+", b"13+", Some(b"2Q"))
+            .is_err());
+
+        let mut saved = vec![];
+        save_as_gfa1_to_writer(&graph, &mut saved).unwrap();
+        let saved = String::from_utf8(saved).unwrap();
+        assert!(saved.contains("L\t11\t+\t12\t-\t2M\n"));
+        assert!(saved.contains("L\t12\t-\t13\t+\t*\n"));
+        assert!(saved.contains("L\t11\t+\t13\t+\t1M1D1M\n"));
+
+        let mut saved = vec![];
+        save_as_gfa2_to_writer(&graph, &mut saved).unwrap();
+        let saved = String::from_utf8(saved).unwrap();
+        assert!(saved.contains("S\t13\t8\t*\tLN:i:8\n"));
+        assert!(saved.contains("E\t*\t11+\t12-\t3\t5$\t4\t6$\t2M\n"));
+        assert!(saved.contains("E\t*\t11+\t13+\t2\t5$\t0\t2\t1M1D1M\n"));
+
+        graph
+            .modify_link(b"11+", b"12-", None, None, Some(b"3M"))
+            .unwrap();
+        let edge = graph.existing_edge(b"12+", b"11-").unwrap();
+        assert_eq!(graph.edge_overlap(edge).unwrap().alignment, "3M");
+        let undo = graph
+            .apply(&"MODIFY LINK 11+ 12- * 13+".parse().unwrap())
+            .unwrap();
+        assert_eq!(undo[0].to_string(), "MODIFY LINK 11+ 13+ 11+ 12-");
+        let edge = graph.existing_edge(b"11+", b"13+").unwrap();
+        assert_eq!(graph.edge_overlap(edge).unwrap().alignment, "3M");
+    }
+
+    #[test]
+    fn can_undo_the_changes_of_an_edge_keeping_its_positions() {
+        use super::super::*;
+        use crate::fileoperation::*;
+
+        // an edge that's not a dovetail overlap, with a trace as alignment
+        let gfa2 = b"H\tVN:Z:2.0\nS\t11\t8\tACCTTGCA\nS\t12\t4\tTTGC\nS\t13\t3\tGCA\n\
+            E\t*\t11+\t12+\t2\t6\t0\t4$\t2,2\tID:Z:inner\n";
+        let (_, mut graph) = load_graph_from_reader(&gfa2[..]).unwrap();
+        let save = |graph: &GFAGraph| {
+            let mut saved = vec![];
+            save_as_gfa2_to_writer(graph, &mut saved).unwrap();
+            String::from_utf8(saved).unwrap()
+        };
+        let before = save(&graph);
+
+        let undo = graph
+            .apply(&"REMOVE LINK 11+ 12+".parse().unwrap())
+            .unwrap();
+        assert_eq!(undo[0].to_string(), "ADD EDGE * 11+ 12+ 2 6 0 4$ 2,2");
+        assert_eq!(undo[0], undo[0].to_string().parse().unwrap());
+        for operation in undo.iter() {
+            graph.apply(operation).unwrap();
+        }
+        assert_eq!(save(&graph), before);
+        for operation in [
+            "REMOVE NODE 12",
+            "MODIFY LINK 11+ 12+ * 13+",
+            "MODIFY LINK 11+ 12+ * * 3M",
+        ] {
+            let mut history = History::new();
+            history
+                .apply(&mut graph, operation.parse().unwrap())
+                .unwrap();
+            history.undo(&mut graph).unwrap();
+            assert_eq!(save(&graph), before);
+        }
+        assert!("ADD EDGE * 11+ 13+ 2 x 0 3$ *"
+            .parse::<EditOperation>()
+            .is_err());
+    }
+
+    #[test]
+    fn can_keep_the_length_of_a_segment_without_sequence() {
+        use super::super::*;
+
+        let gfa2 = b"H\tVN:Z:2.0\nS\t11\t5\tACCTT\nS\t13\t8\t*\nE\t*\t11+\t13+\t5$\t5$\t0\t0\t0M\n";
+        let (_, mut graph) = load_graph_from_reader(&gfa2[..]).unwrap();
+        let node = graph.node_id(b"13").unwrap();
+        assert_eq!(graph.segment_length(node), 8);
+
+        let undo = graph.apply(&"REMOVE NODE 13".parse().unwrap()).unwrap();
+        assert_eq!(undo[0].to_string(), "ADD NODE 13 * 8");
+        assert_eq!(undo[0], undo[0].to_string().parse().unwrap());
+        for operation in undo.iter() {
+            graph.apply(operation).unwrap();
+        }
+        let node = graph.node_id(b"13").unwrap();
+        assert_eq!(graph.segment_length(node), 8);
+
+        // a new segment does not take the length of the one removed
+        graph.remove_node(b"13").unwrap();
+        graph.add_node(b"13", Some(b"*")).unwrap();
+        let node = graph.node_id(b"13").unwrap();
+        assert_eq!(graph.segment_length(node), 0);
+    }
+}
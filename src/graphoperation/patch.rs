@@ -0,0 +1,275 @@
+/// share the edits applied to a graph as a journal that can be replayed on another copy of it
+use std::{fmt, fs};
+
+use super::error::*;
+use super::gfagraph::*;
+use super::history::*;
+use super::operation::*;
+use super::transaction::*;
+
+/// The first line of a journal written by ```to_patch_string```
+pub const PATCH_HEADER: &str = "# handlegfa journal";
+
+/// An entry of a journal (or patch): an operation together with the operations
+/// that undo it, recorded when it was first applied.\
+/// They describe the state of the graph the operation expects to find:
+/// replaying the entry on a graph where they are different is a conflict.
+/// In the text format the operation is written on a line using the grammar of the interactive
+/// command line and every operation of the expected state follows it on a line starting with ```<```
+/// ```text
+/// # handlegfa journal
+/// MODIFY NODE 11 GATTACA
+/// < MODIFY NODE 11 ACCTT
+/// REMOVE LINK 11+ 12-
+/// < ADD LINK 11+ 12-
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchEntry {
+    pub operation: EditOperation,
+    pub expected: Vec<EditOperation>,
+}
+
+impl PatchEntry {
+    /// An entry without expected state, that is applied without checking the graph
+    pub fn new(operation: EditOperation) -> Self {
+        PatchEntry {
+            operation,
+            expected: vec![],
+        }
+    }
+}
+
+impl fmt::Display for PatchEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.operation)?;
+        for operation in self.expected.iter() {
+            write!(f, "\n< {}", operation)?;
+        }
+        Ok(())
+    }
+}
+
+impl Batch {
+    /// The entries of a journal that replay the batch
+    pub fn patch(&self) -> Vec<PatchEntry> {
+        self.operations
+            .iter()
+            .zip(self.undo.iter())
+            .map(|(operation, undo)| PatchEntry {
+                operation: operation.clone(),
+                expected: undo.clone(),
+            })
+            .collect()
+    }
+}
+
+impl History {
+    /// The entries of a journal that replay every operation applied (and not undone)
+    pub fn patch(&self) -> Vec<PatchEntry> {
+        self.iter().flat_map(|batch| batch.patch()).collect()
+    }
+}
+
+/// Write the entries of a journal in the text format, starting with ```PATCH_HEADER```
+pub fn to_patch_string(patch: &[PatchEntry]) -> String {
+    let mut text = format!("{}\n", PATCH_HEADER);
+    for entry in patch.iter() {
+        text.push_str(&format!("{}\n", entry));
+    }
+    text
+}
+
+/// Read the entries of a journal written in the text format.\
+/// Empty lines and lines starting with ```#``` are ignored,
+/// the errors report the line (starting from 1) that cannot be read
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let patch = parse_patch("REMOVE NODE 12\n< ADD NODE 12 TCAAGG").unwrap();
+/// ```
+pub fn parse_patch(text: &str) -> Result<Vec<PatchEntry>, GraphOperationError> {
+    let mut patch: Vec<PatchEntry> = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid =
+            |why: GraphOperationError| GraphOperationError::InvalidPatch(i + 1, why.to_string());
+        if let Some(expected) = line.strip_prefix('<') {
+            let expected = expected.parse::<EditOperation>().map_err(invalid)?;
+            match patch.last_mut() {
+                Some(entry) => entry.expected.push(expected),
+                None => {
+                    return Err(GraphOperationError::InvalidPatch(
+                        i + 1,
+                        "the expected state comes before any operation".to_string(),
+                    ))
+                }
+            }
+        } else {
+            patch.push(PatchEntry::new(
+                line.parse::<EditOperation>().map_err(invalid)?,
+            ));
+        }
+    }
+    Ok(patch)
+}
+
+/// Read a journal from a file
+pub fn read_patch_file(path: String) -> Result<Vec<PatchEntry>, GraphOperationError> {
+    match fs::read_to_string(&path) {
+        Ok(text) => parse_patch(&text),
+        Err(why) => Err(GraphOperationError::Io(path, why)),
+    }
+}
+
+fn sorted(operations: &[EditOperation]) -> Vec<String> {
+    let mut operations: Vec<String> = operations.iter().map(|op| op.to_string()).collect();
+    operations.sort();
+    operations
+}
+
+/// Replay a journal on a graph, all together or not at all.\
+/// If an operation fails, or the graph does not have the state the operation expects,
+/// the operations already applied are undone and the error reports the entry (starting from 1) in conflict
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+/// let patch = read_patch_file("./tests/output_files/journal.patch".to_string()).unwrap();
+/// match apply_patch(&mut graph, &patch) {
+///     Ok(batch) => println!("{} operations applied", batch.operations.len()),
+///     Err(why) => println!("Error: {}", why),
+/// }
+/// ```
+pub fn apply_patch(
+    graph: &mut GFAGraph,
+    patch: &[PatchEntry],
+) -> Result<Batch, GraphOperationError> {
+    let mut transaction = graph.transaction();
+    for (i, entry) in patch.iter().enumerate() {
+        let found = match transaction.apply(entry.operation.clone()) {
+            Ok(undo) => sorted(undo),
+            Err(GraphOperationError::OperationFailed(_, operation, why)) => {
+                return Err(transaction.abort(GraphOperationError::PatchConflict(
+                    i + 1,
+                    operation,
+                    why.to_string(),
+                )))
+            }
+            Err(why) => return Err(transaction.abort(why)),
+        };
+        let expected = sorted(&entry.expected);
+        if !expected.is_empty() && expected != found {
+            return Err(transaction.abort(GraphOperationError::PatchConflict(
+                i + 1,
+                entry.operation.to_string(),
+                format!(
+                    "expected [{}], found [{}]",
+                    expected.join("; "),
+                    found.join("; ")
+                ),
+            )));
+        }
+    }
+    Ok(transaction.commit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    fn record(graph: &mut GFAGraph) -> Vec<PatchEntry> {
+        let mut history = History::new();
+        history
+            .apply(graph, "MODIFY NODE 11 GATTACA".parse().unwrap())
+            .unwrap();
+        history
+            .apply(graph, "remove node 12".parse().unwrap())
+            .unwrap();
+        history
+            .apply(graph, "SET TAG PATH 14 KC:i:3".parse().unwrap())
+            .unwrap();
+        history.patch()
+    }
+
+    #[test]
+    fn can_parse_edit_operations() {
+        let operations = vec![
+            "ADD NODE 16 *",
+            "ADD LINK 16+ 11-",
+            "ADD PATH 20 11+ 13+",
+            "REMOVE NODE 16",
+            "REMOVE LINK 11+ 12-",
+            "REMOVE PATH 14",
+            "MODIFY NODE 11 ACGT",
+            "MODIFY LINK 11+ 12- * 13+",
+            "MODIFY PATH 14 11+ 13+",
+            "SET TAG LINK 11+ 12- KC:i:1",
+            r#"SET TAG NODE 11 "CO:Z:two \"quoted\" words""#,
+            r#"ADD GAP g1 11+ 13+ 10 * "CO:Z:a gap""#,
+            "REMOVE TAG NODE 11 KC",
+        ];
+        for operation in operations {
+            let edit: EditOperation = operation.parse().unwrap();
+            assert_eq!(edit.to_string(), operation);
+        }
+        assert!("MODIFY LINK 11 12- * *".parse::<EditOperation>().is_err());
+        assert!("ADD EDGE 11+ 12-".parse::<EditOperation>().is_err());
+        assert!(r#"SET TAG NODE 11 "CO:Z:not closed"#.parse::<EditOperation>().is_err());
+
+        // a value with spaces is replayed as it was
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        graph.set_node_tag(b"11", br#"CO:Z:a \ "b" c"#).unwrap();
+        let undo = graph
+            .apply(&"REMOVE TAG NODE 11 CO".parse().unwrap())
+            .unwrap();
+        let operation: EditOperation = undo[0].to_string().parse().unwrap();
+        graph.apply(&operation).unwrap();
+        let tags = graph
+            .get_segment_tags(graph.node_id(b"11").unwrap())
+            .unwrap();
+        assert_eq!(tags[0].to_string(), r#"CO:Z:a \ "b" c"#);
+    }
+
+    #[test]
+    fn can_replay_a_journal() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        let patch = parse_patch(&to_patch_string(&record(&mut graph))).unwrap();
+        assert_eq!(patch.len(), 3);
+
+        let mut copy = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        let batch = apply_patch(&mut copy, &patch).unwrap();
+        assert_eq!(batch.operations.len(), 3);
+        assert!(copy.node_id(b"12").is_err());
+        assert_eq!(
+            get_path_tag(&copy, b"14", b"KC").unwrap().to_string(),
+            "KC:i:3"
+        );
+    }
+
+    #[test]
+    fn cannot_replay_a_journal_on_a_different_graph() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        let patch = record(&mut graph);
+
+        let mut copy = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        copy.modify_node(b"12", b"AAAA").unwrap();
+        match apply_patch(&mut copy, &patch) {
+            Err(GraphOperationError::PatchConflict(2, operation, _)) => {
+                assert_eq!(operation, "REMOVE NODE 12")
+            }
+            _ => panic!("the patch should be in conflict"),
+        }
+        // the first entry has been rolled back
+        assert!(apply_patch(&mut copy, &patch[..1]).is_ok());
+
+        match parse_patch("# comment\nREMOVE NODE 12\n< ADD NODE\nREMOVE NODE 11") {
+            Err(GraphOperationError::InvalidPatch(line, _)) => assert_eq!(line, 3),
+            _ => panic!("the patch should not be valid"),
+        }
+    }
+}
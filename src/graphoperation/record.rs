@@ -0,0 +1,641 @@
+/// keep the records the HashGraph cannot store: containments, fragments, gaps and unordered groups
+use bstr::{BString, ByteSlice};
+use gfa2::tag::OptionalFields;
+use std::convert::TryFrom;
+
+use super::error::*;
+use super::gfagraph::*;
+use super::orientedid::*;
+
+/// A containment (```C``` record of ```GFA1```): a segment, with its orientation,
+/// contained in another one from the position ```pos```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Containment {
+    /// the container with its orientation (```11+```)
+    pub container: BString,
+    /// the contained segment with its orientation (```12-```)
+    pub contained: BString,
+    pub pos: usize,
+    pub overlap: BString,
+    pub tags: OptionalFields,
+}
+
+/// A fragment (```F``` record): a part of an external sequence (a read)
+/// aligned to a segment of the graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment {
+    pub segment: BString,
+    /// the external sequence with its orientation (```read1+```)
+    pub external: BString,
+    pub sbeg: BString,
+    pub send: BString,
+    pub fbeg: BString,
+    pub fend: BString,
+    pub alignment: BString,
+    pub tags: OptionalFields,
+}
+
+/// A gap (```G``` record): the estimated distance between 2 segments, with their orientation,
+/// that are not joined by an edge
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gap {
+    pub id: BString,
+    pub from: BString,
+    pub to: BString,
+    pub distance: BString,
+    pub variance: BString,
+    pub tags: OptionalFields,
+}
+
+/// An unordered group (```U``` record): a set of segments, edges or other groups
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnorderedGroup {
+    pub id: BString,
+    pub members: Vec<BString>,
+    pub tags: OptionalFields,
+}
+
+/// Check if a reference with orientation (```utg1+```) points to a segment
+pub fn refers_to(reference: &[u8], segment: &[u8]) -> bool {
+    reference.len() == segment.len() + 1 && reference.starts_with(segment)
+}
+
+// the name and the orientation of a reference with orientation (```utg1+```)
+fn split_orientation(reference: &BString) -> [String; 2] {
+    let (name, orientation) = reference.split_at(reference.len().saturating_sub(1));
+    [
+        name.to_str_lossy().to_string(),
+        orientation.to_str_lossy().to_string(),
+    ]
+}
+
+impl Containment {
+    /// The fields of the record, without the record type and the tags
+    pub fn fields(&self) -> Vec<String> {
+        let mut fields = split_orientation(&self.container).to_vec();
+        fields.extend_from_slice(&split_orientation(&self.contained));
+        fields.push(self.pos.to_string());
+        fields.push(self.overlap.to_string());
+        fields
+    }
+
+    /// Check if the containment involves a segment
+    pub fn touches(&self, segment: &[u8]) -> bool {
+        refers_to(&self.container, segment) || refers_to(&self.contained, segment)
+    }
+}
+
+impl Fragment {
+    /// The fields of the record, without the record type and the tags
+    pub fn fields(&self) -> Vec<String> {
+        [
+            &self.segment,
+            &self.external,
+            &self.sbeg,
+            &self.send,
+            &self.fbeg,
+            &self.fend,
+            &self.alignment,
+        ]
+        .iter()
+        .map(|field| field.to_string())
+        .collect()
+    }
+
+    // the key of the order of the fragments read from the file
+    pub(super) fn key(&self) -> (BString, BString, [BString; 4]) {
+        (
+            self.segment.clone(),
+            self.external.clone(),
+            self.positions(),
+        )
+    }
+
+    /// The positions of the fragment on the segment and on the external sequence.\
+    /// A read can be aligned more than once to the same segment, so they tell its fragments apart
+    pub fn positions(&self) -> [BString; 4] {
+        [
+            self.sbeg.clone(),
+            self.send.clone(),
+            self.fbeg.clone(),
+            self.fend.clone(),
+        ]
+    }
+
+    // the fragment of an external sequence on a segment, at the positions when they are given
+    fn is(&self, segment: &[u8], external: &[u8], positions: Option<&[BString; 4]>) -> bool {
+        self.segment == segment
+            && self.external == external
+            && match positions {
+                Some(positions) => self.positions() == *positions,
+                None => true,
+            }
+    }
+}
+
+/// A gap told by its id, or by its ends when it has none (```*```)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GapReference {
+    Id(BString),
+    Ends(BString, BString),
+}
+
+impl std::fmt::Display for GapReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GapReference::Id(id) => write!(f, "{}", id),
+            GapReference::Ends(from, to) => write!(f, "{} {}", from, to),
+        }
+    }
+}
+
+impl Gap {
+    /// The reference used to find the gap: its id, or its ends when it has none
+    pub fn reference(&self) -> GapReference {
+        if self.id == "*" {
+            GapReference::Ends(self.from.clone(), self.to.clone())
+        } else {
+            GapReference::Id(self.id.clone())
+        }
+    }
+
+    // a gap is found by its id, or by its ends (the first one between them)
+    fn is(&self, reference: &GapReference) -> bool {
+        match reference {
+            GapReference::Id(id) => self.id == *id,
+            GapReference::Ends(from, to) => self.from == *from && self.to == *to,
+        }
+    }
+
+    /// The fields of the record, without the record type and the tags
+    pub fn fields(&self) -> Vec<String> {
+        [
+            &self.id,
+            &self.from,
+            &self.to,
+            &self.distance,
+            &self.variance,
+        ]
+        .iter()
+        .map(|field| field.to_string())
+        .collect()
+    }
+
+    /// Check if the gap starts or ends on a segment
+    pub fn touches(&self, segment: &[u8]) -> bool {
+        refers_to(&self.from, segment) || refers_to(&self.to, segment)
+    }
+}
+
+impl UnorderedGroup {
+    /// The fields of the record, without the record type and the tags
+    pub fn fields(&self) -> Vec<String> {
+        let members: Vec<String> = self
+            .members
+            .iter()
+            .map(|member| member.to_string())
+            .collect();
+        vec![self.id.to_string(), members.join(" ")]
+    }
+}
+
+// the index where a record is added so that the records read from the file keep their order
+// (one removed and added again, as done by undo, goes back to its place),
+// the other ones are appended
+fn input_index<T>(records: &[T], record: &T, rank: impl Fn(&T) -> Option<usize>) -> usize {
+    let rank = |record: &T| rank(record).unwrap_or(usize::MAX);
+    let index = rank(record);
+    records
+        .iter()
+        .position(|other| rank(other) > index)
+        .unwrap_or(records.len())
+}
+
+fn record_not_exist(record: &str, fields: &[&[u8]]) -> GraphOperationError {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| field.to_str_lossy().to_string())
+        .collect();
+    GraphOperationError::RecordNotExist(format!("{} {}", record, fields.join(" ")))
+}
+
+/// The containments, fragments, gaps and unordered groups are edited like the other elements of the graph:
+/// every operation checks its arguments before touching the graph,
+/// so if an error is returned the graph is left unchanged
+impl GFAGraph {
+    /// Add a containment between 2 existing segments, with their orientation
+    pub fn add_containment(&mut self, containment: Containment) -> Result<(), GraphOperationError> {
+        self.oriented_handle(&containment.container)?;
+        self.oriented_handle(&containment.contained)?;
+        if self
+            .containment(&containment.container, &containment.contained)
+            .is_some()
+        {
+            return Err(GraphOperationError::IdAlreadyExist(format!(
+                "{} {}",
+                containment.container, containment.contained
+            )));
+        }
+        let input = &self.input_order.containments;
+        let i = input_index(&self.containments, &containment, |containment| {
+            input
+                .get(&(containment.container.clone(), containment.contained.clone()))
+                .copied()
+        });
+        self.containments.insert(i, containment);
+        Ok(())
+    }
+
+    /// Remove the containment of a segment in another one, with their orientation
+    pub fn remove_containment(
+        &mut self,
+        container: &[u8],
+        contained: &[u8],
+    ) -> Result<(), GraphOperationError> {
+        match self.containment_position(container, contained) {
+            Some(i) => {
+                self.containments.remove(i);
+                Ok(())
+            }
+            None => Err(record_not_exist("C", &[container, contained])),
+        }
+    }
+
+    /// Return the containment of a segment in another one, with their orientation
+    pub fn containment(&self, container: &[u8], contained: &[u8]) -> Option<&Containment> {
+        self.containment_position(container, contained)
+            .map(|i| &self.containments[i])
+    }
+
+    fn containment_position(&self, container: &[u8], contained: &[u8]) -> Option<usize> {
+        self.containments.iter().position(|containment| {
+            containment.container == container && containment.contained == contained
+        })
+    }
+
+    /// Add a fragment to an existing segment
+    pub fn add_fragment(&mut self, fragment: Fragment) -> Result<(), GraphOperationError> {
+        self.node_id(&fragment.segment)?;
+        OrientedId::try_from(fragment.external.as_bytes())?;
+        let positions = fragment.positions();
+        if self
+            .fragments
+            .iter()
+            .any(|other| other.is(&fragment.segment, &fragment.external, Some(&positions)))
+        {
+            return Err(GraphOperationError::IdAlreadyExist(
+                fragment.fields()[..6].join(" "),
+            ));
+        }
+        let input = &self.input_order.fragments;
+        let i = input_index(&self.fragments, &fragment, |fragment| {
+            input.get(&fragment.key()).copied()
+        });
+        self.fragments.insert(i, fragment);
+        Ok(())
+    }
+
+    /// Remove the fragment of an external sequence (with orientation) aligned to a segment,
+    /// at the positions given (they can be left out when the sequence is aligned only once to the segment)
+    pub fn remove_fragment(
+        &mut self,
+        segment: &[u8],
+        external: &[u8],
+        positions: Option<&[BString; 4]>,
+    ) -> Result<(), GraphOperationError> {
+        let i = self.fragment_position(segment, external, positions)?;
+        self.fragments.remove(i);
+        Ok(())
+    }
+
+    /// Return the fragment of an external sequence (with orientation) aligned to a segment,
+    /// at the positions given (they can be left out when the sequence is aligned only once to the segment)
+    pub fn fragment(
+        &self,
+        segment: &[u8],
+        external: &[u8],
+        positions: Option<&[BString; 4]>,
+    ) -> Option<&Fragment> {
+        self.fragment_position(segment, external, positions)
+            .ok()
+            .map(|i| &self.fragments[i])
+    }
+
+    fn fragment_position(
+        &self,
+        segment: &[u8],
+        external: &[u8],
+        positions: Option<&[BString; 4]>,
+    ) -> Result<usize, GraphOperationError> {
+        let mut found = self
+            .fragments
+            .iter()
+            .enumerate()
+            .filter(|(_, fragment)| fragment.is(segment, external, positions))
+            .map(|(i, _)| i);
+        match (found.next(), found.next()) {
+            (Some(i), None) => Ok(i),
+            (Some(_), Some(_)) => Err(GraphOperationError::AmbiguousRecord(format!(
+                "F {} {}",
+                segment.to_str_lossy(),
+                external.to_str_lossy()
+            ))),
+            (None, _) => {
+                let mut fields = vec![segment, external];
+                if let Some(positions) = positions {
+                    fields.extend(positions.iter().map(|position| position.as_bytes()));
+                }
+                Err(record_not_exist("F", &fields))
+            }
+        }
+    }
+
+    /// Add a gap between 2 existing segments, with their orientation
+    pub fn add_gap(&mut self, gap: Gap) -> Result<(), GraphOperationError> {
+        self.oriented_handle(&gap.from)?;
+        self.oriented_handle(&gap.to)?;
+        if gap.id != "*" && self.gaps.iter().any(|other| other.id == gap.id) {
+            return Err(GraphOperationError::IdAlreadyExist(gap.id.to_string()));
+        }
+        let input = &self.input_order.gaps;
+        let i = input_index(&self.gaps, &gap, |gap| input.get(&gap.reference()).copied());
+        self.gaps.insert(i, gap);
+        Ok(())
+    }
+
+    /// Remove a gap, found by its id or by its ends
+    pub fn remove_gap(&mut self, gap: &GapReference) -> Result<(), GraphOperationError> {
+        match self.gap_position(gap) {
+            Some(i) => {
+                self.gaps.remove(i);
+                Ok(())
+            }
+            None => Err(GraphOperationError::RecordNotExist(format!("G {}", gap))),
+        }
+    }
+
+    /// Return a gap, found by its id or by its ends
+    pub fn gap(&self, gap: &GapReference) -> Option<&Gap> {
+        self.gap_position(gap).map(|i| &self.gaps[i])
+    }
+
+    fn gap_position(&self, gap: &GapReference) -> Option<usize> {
+        self.gaps.iter().position(|other| other.is(gap))
+    }
+
+    /// Add an unordered group with a new id.\
+    /// The members can be segments, edges or other groups, so they are not checked
+    pub fn add_group(&mut self, group: UnorderedGroup) -> Result<(), GraphOperationError> {
+        if group.members.is_empty() {
+            return Err(GraphOperationError::EmptyId);
+        }
+        if self.group(&group.id).is_some() {
+            return Err(GraphOperationError::IdAlreadyExist(group.id.to_string()));
+        }
+        let input = &self.input_order.groups;
+        let i = input_index(&self.groups, &group, |group| input.get(&group.id).copied());
+        self.groups.insert(i, group);
+        Ok(())
+    }
+
+    /// Remove an unordered group
+    pub fn remove_group(&mut self, id: &[u8]) -> Result<(), GraphOperationError> {
+        match self.groups.iter().position(|group| group.id == id) {
+            Some(i) => {
+                self.groups.remove(i);
+                Ok(())
+            }
+            None => Err(record_not_exist("U", &[id])),
+        }
+    }
+
+    /// Replace the members of an unordered group
+    pub fn modify_group(
+        &mut self,
+        id: &[u8],
+        members: Vec<&[u8]>,
+    ) -> Result<(), GraphOperationError> {
+        if members.is_empty() {
+            return Err(GraphOperationError::EmptyId);
+        }
+        match self.groups.iter_mut().find(|group| group.id == id) {
+            Some(group) => {
+                group.members = members.into_iter().map(BString::from).collect();
+                Ok(())
+            }
+            None => Err(record_not_exist("U", &[id])),
+        }
+    }
+
+    /// Return an unordered group
+    pub fn group(&self, id: &[u8]) -> Option<&UnorderedGroup> {
+        self.groups.iter().find(|group| group.id == id)
+    }
+
+    // a removed segment takes away its containments, fragments and gaps, and leaves the groups
+    // (a group left without members is removed too)
+    pub(super) fn remove_segment_records(&mut self, segment: &[u8]) {
+        self.containments
+            .retain(|containment| !containment.touches(segment));
+        self.fragments
+            .retain(|fragment| fragment.segment != segment);
+        self.gaps.retain(|gap| !gap.touches(segment));
+        for group in self.groups.iter_mut() {
+            group.members.retain(|member| member != segment);
+        }
+        self.groups.retain(|group| !group.members.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use crate::fileoperation::save_as_gfa2_to_writer;
+
+    const GFA2: &[u8] = b"H\tVN:Z:2.0\nS\t1\t3\tACG\nS\t2\t3\tTTT\nS\t3\t2\tCA\n\
+        F\t2\tread1+\t0\t3\t12\t15$\t*\tid:Z:read1_in_2\n\
+        E\t*\t1+\t2+\t3$\t3$\t0\t0\t0M\nG\tg1\t2+\t3-\t120\t*\n\
+        U\tu1\t1 2\nU\tu2\t2\n";
+
+    #[test]
+    fn can_keep_fragments_gaps_and_groups() {
+        let (_, mut graph) = load_graph_from_reader(GFA2).unwrap();
+        assert_eq!(graph.fragments.len(), 1);
+        assert_eq!(
+            graph
+                .gap(&GapReference::Ends("2+".into(), "3-".into()))
+                .unwrap()
+                .distance,
+            "120"
+        );
+        assert_eq!(graph.group(b"u1").unwrap().members, ["1", "2"]);
+
+        let mut saved = vec![];
+        save_as_gfa2_to_writer(&graph, &mut saved).unwrap();
+        let saved = String::from_utf8(saved).unwrap();
+        assert!(saved.contains("F\t2\tread1+\t0\t3\t12\t15$\t*\tid:Z:read1_in_2\n"));
+        assert!(saved.contains("G\tg1\t2+\t3-\t120\t*\n"));
+        assert!(saved.contains("U\tu1\t1 2\n"));
+
+        let mut history = History::new();
+        history
+            .apply(&mut graph, EditOperation::RemoveNode { id: "2".into() })
+            .unwrap();
+        assert!(graph.fragments.is_empty());
+        assert!(graph.gaps.is_empty());
+        assert_eq!(graph.group(b"u1").unwrap().members, ["1"]);
+        assert!(graph.group(b"u2").is_none());
+
+        history.undo(&mut graph).unwrap();
+        assert_eq!(graph.fragments.len(), 1);
+        assert_eq!(graph.gaps.len(), 1);
+        assert_eq!(graph.group(b"u1").unwrap().members, ["1", "2"]);
+        assert_eq!(graph.group(b"u2").unwrap().members, ["2"]);
+    }
+
+    #[test]
+    fn can_keep_the_order_of_the_records_after_undo() {
+        const RECORDS: &[u8] = b"H\tVN:Z:2.0\nS\t1\t3\tACG\nS\t2\t3\tTTT\nS\t3\t2\tCA\n\
+            F\t2\tread1+\t0\t3\t12\t15$\t*\nF\t3\tread2+\t0\t2\t0\t2\t*\n\
+            G\tg1\t2+\t3-\t120\t*\nG\tg2\t1+\t3+\t80\t*\nU\tu1\t2\nU\tu2\t1 3\n";
+        let (_, mut graph) = load_graph_from_reader(RECORDS).unwrap();
+        let save = |graph: &GFAGraph| {
+            let mut saved = vec![];
+            save_as_gfa2_to_writer(graph, &mut saved).unwrap();
+            String::from_utf8(saved).unwrap()
+        };
+        let before = save(&graph);
+
+        let mut history = History::new();
+        history
+            .apply(&mut graph, EditOperation::RemoveNode { id: "2".into() })
+            .unwrap();
+        history.undo(&mut graph).unwrap();
+        assert_eq!(save(&graph), before);
+
+        // a record that has not been read from the file is appended
+        graph
+            .add_group(UnorderedGroup {
+                id: "u0".into(),
+                members: vec!["1".into()],
+                tags: Default::default(),
+            })
+            .unwrap();
+        assert_eq!(graph.groups.last().unwrap().id, "u0");
+    }
+
+    #[test]
+    fn can_undo_the_removal_of_a_segment_with_several_fragments_of_a_read() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/data.gfa2".to_string()).unwrap();
+        let save = |graph: &GFAGraph| {
+            let mut saved = vec![];
+            save_as_gfa2_to_writer(graph, &mut saved).unwrap();
+            String::from_utf8(saved).unwrap()
+        };
+        let before = save(&graph);
+
+        let mut history = History::new();
+        history
+            .apply(&mut graph, EditOperation::RemoveNode { id: "16".into() })
+            .unwrap();
+        assert!(graph
+            .fragments
+            .iter()
+            .all(|fragment| fragment.segment != "16"));
+        history.undo(&mut graph).unwrap();
+        assert_eq!(save(&graph), before);
+
+        // the read is aligned twice to the segment, so the positions tell the fragment
+        assert!(matches!(
+            graph.apply(&"REMOVE FRAGMENT 16 2+".parse().unwrap()),
+            Err(GraphOperationError::AmbiguousRecord(_))
+        ));
+        let operation: EditOperation = "REMOVE FRAGMENT 16 2+ 0 70$ 420 490$".parse().unwrap();
+        assert_eq!(
+            operation.to_string(),
+            "REMOVE FRAGMENT 16 2+ 0 70$ 420 490$"
+        );
+        let undo = graph.apply(&operation).unwrap();
+        assert_eq!(undo[0].to_string(), "ADD FRAGMENT 16 2+ 0 70$ 420 490$ 11M");
+        assert!(graph.fragment(b"16", b"2+", None).is_some());
+    }
+
+    #[test]
+    fn can_keep_the_edges_referred_by_the_groups() {
+        const GROUPS: &[u8] = b"H\tVN:Z:2.0\nS\t1\t3\tACG\nS\t2\t3\tTTT\nS\t3\t2\tCA\n\
+            E\t1_to_2\t1+\t2+\t3$\t3$\t0\t0\t0M\nE\t*\t2+\t3+\t3$\t3$\t0\t0\t0M\n\
+            O\tp1\t1+ 1_to_2+ 2+ 3+\nU\tu1\t3 1_to_2\n";
+        let save = |graph: &GFAGraph| {
+            let mut saved = vec![];
+            save_as_gfa2_to_writer(graph, &mut saved).unwrap();
+            String::from_utf8(saved).unwrap()
+        };
+        let (_, graph) = load_graph_from_reader(GROUPS).unwrap();
+        let (lenient, warnings) = gfa2_to_handlegraph_from_reader_lenient(GROUPS).unwrap();
+        assert!(warnings.is_empty());
+        for mut graph in [graph, lenient] {
+            // the edge is not taken for a segment
+            assert!(graph.node_id(b"1_to_2").is_err());
+            let saved = save(&graph);
+            assert!(saved.contains("E\t1_to_2\t1+\t2+\t3$\t3$\t0\t0\t0M\n"));
+            assert!(saved.contains("E\t*\t2+\t3+\t"));
+            assert!(saved.contains("O\tp1\t1+ 2+ 3+\n"));
+            assert!(saved.contains("U\tu1\t3 1_to_2\n"));
+
+            // the id follows the link and comes back with it
+            let mut history = History::new();
+            history
+                .apply(&mut graph, "MODIFY LINK 1+ 2+ * 3-".parse().unwrap())
+                .unwrap();
+            assert_eq!(
+                graph.edge_id(graph.existing_edge(b"1+", b"3-").unwrap()),
+                "1_to_2"
+            );
+            history.undo(&mut graph).unwrap();
+            let undo = graph.apply(&"REMOVE NODE 2".parse().unwrap()).unwrap();
+            assert_eq!(undo[1].to_string(), "ADD EDGE 1_to_2 1+ 2+ 3$ 3$ 0 0 0M");
+            for operation in undo.iter() {
+                graph.apply(operation).unwrap();
+            }
+            assert_eq!(save(&graph), saved);
+            assert!(graph
+                .apply(&"ADD EDGE 1_to_2 1+ 3+ 3$ 3$ 0 0 0M".parse().unwrap())
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn can_edit_fragments_gaps_and_groups() {
+        let (_, mut graph) = load_graph_from_reader(GFA2).unwrap();
+        let operation: EditOperation = "ADD GAP * 1+ 3+ 50 5 xx:i:1".parse().unwrap();
+        assert_eq!(operation.to_string(), "ADD GAP * 1+ 3+ 50 5 xx:i:1");
+        graph.apply(&operation).unwrap();
+        assert_eq!(graph.gaps.len(), 2);
+
+        // a gap with an id is removed by its id, the other ones by their ends
+        let undo = graph.apply(&"REMOVE GAP g1".parse().unwrap()).unwrap();
+        assert_eq!(undo[0].to_string(), "ADD GAP g1 2+ 3- 120 *");
+        let undo = graph.apply(&undo[0]).unwrap();
+        assert_eq!(undo[0].to_string(), "REMOVE GAP g1");
+        let undo = graph.apply(&"REMOVE GAP 1+ 3+".parse().unwrap()).unwrap();
+        assert_eq!(undo[0].to_string(), "ADD GAP * 1+ 3+ 50 5 xx:i:1");
+        let undo = graph.apply(&undo[0]).unwrap();
+        assert_eq!(undo[0].to_string(), "REMOVE GAP 1+ 3+");
+
+        let operation: EditOperation = "ADD GROUP u3 1 3 xx:Z:set".parse().unwrap();
+        graph.apply(&operation).unwrap();
+        assert_eq!(graph.group(b"u3").unwrap().members, ["1", "3"]);
+        assert_eq!(graph.group(b"u3").unwrap().tags.len(), 1);
+
+        let undo = graph.apply(&"MODIFY GROUP u3 3".parse().unwrap()).unwrap();
+        assert_eq!(undo[0].to_string(), "MODIFY GROUP u3 1 3");
+
+        assert!(graph
+            .apply(&"REMOVE FRAGMENT 1 read1+".parse().unwrap())
+            .is_err());
+        assert!(graph
+            .apply(&"ADD FRAGMENT 4 read2- 0 3 0 3 *".parse().unwrap())
+            .is_err());
+        assert!(graph.apply(&"REMOVE GROUP u4".parse().unwrap()).is_err());
+    }
+}
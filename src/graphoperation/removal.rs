@@ -0,0 +1,417 @@
+/// remove a node keeping the paths that walk through it coherent
+use bstr::{BString, ByteSlice};
+use handlegraph2::{
+    handle::{Direction, Edge, Handle},
+    handlegraph::HandleGraph,
+};
+use std::{collections::HashSet, fmt};
+
+use super::error::*;
+use super::gfagraph::*;
+use super::operation::*;
+use super::walk::*;
+
+/// What happens to the paths that walk through a node when it's removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeRemoval {
+    /// the node is not removed if a path walks through it
+    #[default]
+    Refuse,
+    /// every path is split at the steps on the node, the sub-paths are named
+    /// after the path (```14_1```, ```14_2```, ...) and keep its tags and its haplotype,
+    /// with the range of the sequence they cover (computed from the lengths of the segments).
+    /// A circular path is cut as a linear one, from its first step
+    Split,
+    /// the steps on the node are dropped and the steps around them are joined by a new (blunt) link,
+    /// when there is not one already. A circular path that starts on the node is closed again
+    /// from its new last step to its new first one.\
+    /// The haplotype of a path that loses some steps keeps the range from its first step kept,
+    /// as long as the sequence of the steps kept
+    Bridge,
+}
+
+/// The paths and the links changed removing a node, the links written with orientation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RemovalReport {
+    /// the links of the node
+    pub removed_links: Vec<(BString, BString)>,
+    /// the links that bridge the steps on the node
+    pub added_links: Vec<(BString, BString)>,
+    /// the paths that have lost some steps
+    pub modified_paths: Vec<BString>,
+    /// the paths split or left without steps
+    pub removed_paths: Vec<BString>,
+    /// the sub-paths of the paths split
+    pub added_paths: Vec<BString>,
+}
+
+impl fmt::Display for RemovalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = vec![];
+        for (from, to) in self.removed_links.iter() {
+            lines.push(format!("Removed link: {} -> {}", from, to));
+        }
+        for (from, to) in self.added_links.iter() {
+            lines.push(format!("Added link: {} -> {}", from, to));
+        }
+        for path in self.modified_paths.iter() {
+            lines.push(format!("Modified path: {}", path));
+        }
+        for path in self.removed_paths.iter() {
+            lines.push(format!("Removed path: {}", path));
+        }
+        for path in self.added_paths.iter() {
+            lines.push(format!("Added path: {}", path));
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// The changes to the paths are operations of their own,
+/// so they are undone and recorded in a journal together with the removal of the node
+impl GFAGraph {
+    /// The operations that remove a node changing the paths that walk through it as asked,
+    /// with the report of the paths and the links they change.
+    /// With ```NodeRemoval::Refuse``` a path on the node is a ```NodeInPaths``` error
+    /// # Example
+    /// ```ignore
+    /// use handle_gfa::graphoperation::*;
+    ///
+    /// let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+    /// let (operations, report) = graph.remove_node_operations(b"12", NodeRemoval::Bridge).unwrap();
+    /// graph.apply_batch(operations).unwrap();
+    /// println!("{}", report);
+    /// ```
+    pub fn remove_node_operations(
+        &self,
+        nodeid: &[u8],
+        policy: NodeRemoval,
+    ) -> Result<(Vec<EditOperation>, RemovalReport), GraphOperationError> {
+        let node = self.node_id(nodeid)?;
+        let name = |handle: Handle| BString::from(self.oriented_id(handle).to_string());
+        let mut report = RemovalReport::default();
+
+        let handle = Handle::pack(node, false);
+        let mut edges = HashSet::new();
+        for next in self.graph.handle_edges_iter(handle, Direction::Right) {
+            edges.insert(edge_key(Edge(handle, next)));
+        }
+        for prev in self.graph.handle_edges_iter(handle, Direction::Left) {
+            edges.insert(edge_key(Edge(prev, handle)));
+        }
+        let mut edges: Vec<Edge> = edges.into_iter().collect();
+        edges.sort();
+        report.removed_links = edges
+            .into_iter()
+            .map(|Edge(left, right)| (name(left), name(right)))
+            .collect();
+
+        let mut paths: Vec<_> = self
+            .graph
+            .paths
+            .iter()
+            .filter(|(_, path)| path.nodes.iter().any(|step| step.id() == node))
+            .collect();
+        paths.sort_by_key(|(id, _)| **id);
+        if policy == NodeRemoval::Refuse && !paths.is_empty() {
+            return Err(GraphOperationError::NodeInPaths(
+                nodeid.to_str_lossy().to_string(),
+                paths
+                    .iter()
+                    .map(|(_, path)| path.name.to_str_lossy().to_string())
+                    .collect(),
+            ));
+        }
+
+        let mut operations = vec![];
+        let mut bridges = HashSet::new();
+        let mut used: HashSet<BString> = self
+            .graph
+            .paths
+            .values()
+            .map(|path| BString::from(path.name.as_slice()))
+            .collect();
+        for (_, path) in paths {
+            let path_name = BString::from(path.name.as_slice());
+            // the steps between the ones on the node, with the index of their first step
+            let mut pieces: Vec<(usize, &[Handle])> = vec![];
+            let mut begin = 0;
+            for end in 0..=path.nodes.len() {
+                if end == path.nodes.len() || path.nodes[end].id() == node {
+                    if begin < end {
+                        pieces.push((begin, &path.nodes[begin..end]));
+                    }
+                    begin = end + 1;
+                }
+            }
+            // the position of every step in the sequence of the path
+            let mut positions = vec![0];
+            for step in path.nodes.iter() {
+                positions.push(positions[positions.len() - 1] + self.segment_length(step.id()));
+            }
+            let steps: Vec<Handle> = match policy {
+                NodeRemoval::Split if pieces.len() > 1 => {
+                    operations.push(EditOperation::RemovePath {
+                        name: path_name.clone(),
+                    });
+                    report.removed_paths.push(path_name.clone());
+                    let mut number = 0;
+                    for (begin, piece) in pieces {
+                        let piece_name = loop {
+                            number += 1;
+                            let piece_name = BString::from(format!("{}_{}", path_name, number));
+                            if !used.contains(&piece_name) {
+                                break piece_name;
+                            }
+                        };
+                        used.insert(piece_name.clone());
+                        // the sub-paths keep the steps of the path as they were
+                        operations.push(EditOperation::AddPath {
+                            name: piece_name.clone(),
+                            steps: piece.iter().map(|step| name(*step)).collect(),
+                            links: PathLinks::Unchecked,
+                        });
+                        for field in self.get_path_tags(&path.name).into_iter().flatten() {
+                            operations.push(EditOperation::SetTag {
+                                target: TagTarget::Path(piece_name.clone()),
+                                field: BString::from(field.to_string()),
+                            });
+                        }
+                        // the sub-path of a walk covers only a part of its sequence
+                        if let Some(walk) = self.walk(&path.name) {
+                            let start = walk.start.map(|start| start + positions[begin]);
+                            let length = positions[begin + piece.len()] - positions[begin];
+                            operations.push(EditOperation::SetWalk {
+                                name: piece_name.clone(),
+                                walk: Walk {
+                                    start,
+                                    end: start.map(|start| start + length),
+                                    ..walk.clone()
+                                },
+                            });
+                        }
+                        report.added_paths.push(piece_name);
+                    }
+                    continue;
+                }
+                NodeRemoval::Bridge => {
+                    let mut gaps: Vec<Edge> = pieces
+                        .windows(2)
+                        .map(|pair| Edge(*pair[0].1.last().unwrap(), pair[1].1[0]))
+                        .collect();
+                    // a circular path ends with its first step: when that's on the node
+                    // the path is closed again from its new last step to its new first one
+                    let mut closing = None;
+                    if let (true, Some(start), Some((_, first)), Some((_, last))) = (
+                        path.is_circular,
+                        path.nodes.first(),
+                        pieces.first(),
+                        pieces.last(),
+                    ) {
+                        if start.id() == node {
+                            gaps.push(Edge(*last.last().unwrap(), first[0]));
+                            closing = Some(first[0]);
+                        }
+                    }
+                    for edge in gaps {
+                        if !self.has_edge(edge) && bridges.insert(edge_key(edge)) {
+                            let (from, to) = (name(edge.0), name(edge.1));
+                            operations.push(EditOperation::AddLink {
+                                from: from.clone(),
+                                to: to.clone(),
+                                overlap: None,
+                            });
+                            report.added_links.push((from, to));
+                        }
+                    }
+                    pieces
+                        .iter()
+                        .flat_map(|(_, piece)| piece.iter())
+                        .copied()
+                        .chain(closing)
+                        .collect()
+                }
+                _ => pieces
+                    .iter()
+                    .flat_map(|(_, piece)| piece.iter())
+                    .copied()
+                    .collect(),
+            };
+            if steps.is_empty() {
+                operations.push(EditOperation::RemovePath {
+                    name: path_name.clone(),
+                });
+                report.removed_paths.push(path_name);
+            } else {
+                let length: usize = steps
+                    .iter()
+                    .map(|step| self.segment_length(step.id()))
+                    .sum();
+                operations.push(EditOperation::ModifyPath {
+                    name: path_name.clone(),
+                    steps: steps.into_iter().map(name).collect(),
+                });
+                // the haplotype covers only the sequence of the steps kept,
+                // from the first one (the steps dropped in between are not counted)
+                if let (Some(walk), Some((begin, _))) = (self.walk(&path.name), pieces.first()) {
+                    let start = walk.start.map(|start| start + positions[*begin]);
+                    let kept = Walk {
+                        start,
+                        end: start.map(|start| start + length),
+                        ..walk.clone()
+                    };
+                    if kept != *walk {
+                        operations.push(EditOperation::SetWalk {
+                            name: path_name.clone(),
+                            walk: kept,
+                        });
+                    }
+                }
+                report.modified_paths.push(path_name);
+            }
+        }
+        operations.push(EditOperation::RemoveNode {
+            id: BString::from(nodeid),
+        });
+        Ok((operations, report))
+    }
+
+    /// Remove a node changing the paths that walk through it as asked
+    /// (see ```remove_node_operations```), if an error is returned the graph is left unchanged
+    pub fn remove_node_with_paths(
+        &mut self,
+        nodeid: &[u8],
+        policy: NodeRemoval,
+    ) -> Result<RemovalReport, GraphOperationError> {
+        let (operations, report) = self.remove_node_operations(nodeid, policy)?;
+        self.apply_batch(operations)?;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use crate::fileoperation::save_as_gfa1_to_writer;
+    use bstr::BString;
+
+    // the graph as it's saved, to compare it regardless of the order of its maps
+    fn saved(graph: &GFAGraph) -> String {
+        let mut saved = vec![];
+        save_as_gfa1_to_writer(graph, &mut saved).unwrap();
+        String::from_utf8(saved).unwrap()
+    }
+
+    #[test]
+    fn can_remove_a_node_repairing_the_paths() {
+        let gfa1 = b"H\tVN:Z:1.0\nS\t11\tACCTT\nS\t12\tTCAAGG\nS\t13\tCTTGATT\n\
+            L\t11\t+\t12\t-\t0M\nL\t12\t-\t13\t+\t0M\nL\t11\t+\t13\t+\t0M\n\
+            P\t14\t11+,12-,13+\t*\nP\t15\t11+,13+\t*\nP\t16\t12-,13+\t*\n";
+        let (_, mut graph) = load_graph_from_reader(&gfa1[..]).unwrap();
+
+        let before = saved(&graph);
+        match graph.remove_node_with_paths(b"12", NodeRemoval::Refuse) {
+            Err(GraphOperationError::NodeInPaths(node, paths)) => {
+                assert_eq!(node, "12");
+                assert_eq!(paths, ["14", "16"]);
+            }
+            _ => panic!("the node is used by 2 paths"),
+        }
+        assert_eq!(before, saved(&graph));
+
+        let (_, report) = graph
+            .remove_node_operations(b"12", NodeRemoval::Split)
+            .unwrap();
+        assert_eq!(report.removed_paths, ["14"]);
+        assert_eq!(report.added_paths, ["14_1", "14_2"]);
+        assert_eq!(report.modified_paths, ["16"]);
+
+        let mut history = History::new();
+        let (operations, report) = graph
+            .remove_node_operations(b"12", NodeRemoval::Bridge)
+            .unwrap();
+        assert_eq!(report.removed_links.len(), 2);
+        assert!(report.added_links.is_empty());
+        assert_eq!(report.modified_paths, ["14", "16"]);
+        history.apply_batch(&mut graph, operations).unwrap();
+        assert!(validate(&graph.graph).is_valid());
+        history.undo(&mut graph).unwrap();
+        assert!(graph.node_id(b"12").is_ok());
+
+        graph.remove_link(b"11+", b"13+").unwrap();
+        let report = graph
+            .remove_node_with_paths(b"12", NodeRemoval::Bridge)
+            .unwrap();
+        assert_eq!(report.added_links, [("11+".into(), "13+".into())]);
+        assert!(validate(&graph.graph).is_valid());
+        assert_eq!(
+            graph.apply(&"REMOVE PATH 14".parse().unwrap()).unwrap()[0].to_string(),
+            "ADD PATH 14 11+ 13+ UNCHECKED"
+        );
+    }
+
+    #[test]
+    fn can_split_the_paths_keeping_tags_and_haplotypes() {
+        let gfa1 = b"H\tVN:Z:1.1\nS\t11\tACCTT\nS\t12\tTCAAGG\nS\t13\tCTTGATT\n\
+            L\t11\t+\t12\t-\t0M\nL\t12\t-\t13\t+\t0M\n\
+            W\tHG1\t1\tchr1\t10\t28\t>11<12>13\tKC:i:3\n";
+        let (_, mut graph) = load_graph_from_reader(&gfa1[..]).unwrap();
+
+        let report = graph
+            .remove_node_with_paths(b"12", NodeRemoval::Split)
+            .unwrap();
+        assert_eq!(report.removed_paths, ["HG1#1#chr1[10-28]"]);
+        let expected = [
+            (b"HG1#1#chr1[10-28]_1", 10, 15),
+            (b"HG1#1#chr1[10-28]_2", 21, 28),
+        ];
+        for (name, start, end) in expected.iter() {
+            assert!(report.added_paths.contains(&BString::from(&name[..])));
+            let walk = graph.walk(&name[..]).unwrap();
+            assert_eq!((walk.sample.as_slice(), walk.haplotype), (&b"HG1"[..], 1));
+            assert_eq!((walk.start, walk.end), (Some(*start), Some(*end)));
+            let tags = graph.get_path_tags(&name[..]).unwrap();
+            assert_eq!(tags[0].to_string(), "KC:i:3");
+        }
+    }
+
+    #[test]
+    fn can_shorten_the_haplotype_of_a_path() {
+        let gfa1 = b"H\tVN:Z:1.1\nS\t11\tACCTT\nS\t12\tTCAAGG\nS\t13\tCTTGATT\n\
+            L\t11\t+\t12\t-\t0M\nL\t12\t-\t13\t+\t0M\n\
+            W\tHG1\t1\tchr1\t10\t28\t>11<12>13\n";
+        let name = &b"HG1#1#chr1[10-28]"[..];
+        // the node removed from an end of the path, and from its middle
+        for (node, policy, range) in [
+            (&b"11"[..], NodeRemoval::Split, (15, 28)),
+            (&b"13"[..], NodeRemoval::Split, (10, 21)),
+            (&b"12"[..], NodeRemoval::Bridge, (10, 22)),
+        ] {
+            let (_, mut graph) = load_graph_from_reader(&gfa1[..]).unwrap();
+            let report = graph.remove_node_with_paths(node, policy).unwrap();
+            assert_eq!(report.modified_paths, [BString::from(name)]);
+            let walk = graph.walk(name).unwrap();
+            assert_eq!((walk.start, walk.end), (Some(range.0), Some(range.1)));
+        }
+    }
+
+    #[test]
+    fn can_bridge_a_circular_path() {
+        let gfa1 = b"H\tVN:Z:1.0\nS\t11\tACCTT\nS\t12\tTCAAGG\nS\t13\tCTTGATT\n\
+            L\t11\t+\t12\t-\t0M\nL\t12\t-\t13\t+\t0M\nL\t13\t+\t11\t+\t0M\n";
+        let (_, mut graph) = load_graph_from_reader(&gfa1[..]).unwrap();
+        graph
+            .add_path(Some(b"14"), vec![b"11+", b"12-", b"13+", b"11+"])
+            .unwrap();
+        assert!(validate(&graph.graph).is_valid());
+
+        let report = graph
+            .remove_node_with_paths(b"11", NodeRemoval::Bridge)
+            .unwrap();
+        assert_eq!(report.modified_paths, ["14"]);
+        assert_eq!(report.added_links, [("13+".into(), "12-".into())]);
+        assert!(validate(&graph.graph).is_valid());
+        let undo = graph.apply(&"REMOVE PATH 14".parse().unwrap()).unwrap();
+        assert_eq!(undo[0].to_string(), "ADD PATH 14 12- 13+ 12- UNCHECKED");
+    }
+}
@@ -0,0 +1,121 @@
+/// apply a file of edits written with the grammar of the interactive command line
+use std::fs;
+
+use super::error::*;
+use super::gfagraph::*;
+use super::operation::*;
+use super::transaction::*;
+
+/// The result of an edit script: the operations applied
+/// and the errors of the lines that cannot be applied (```LineFailed```)
+#[derive(Debug, Default)]
+pub struct ScriptReport {
+    pub batch: Batch,
+    pub errors: Vec<GraphOperationError>,
+}
+
+impl ScriptReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Apply an edit script, one operation per line (```ADD NODE 42 ACGT```, ```REMOVE LINK 11+ 13+```...).\
+/// Empty lines and lines starting with ```#``` are ignored.
+/// With ```keep_going``` the lines that cannot be applied are skipped and reported,
+/// otherwise the script stops at the first error and the graph is left unchanged
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+/// let report = run_script(&mut graph, "ADD NODE 42 ACGT\nADD LINK 13+ 42+", false);
+/// for error in report.errors.iter() {
+///     println!("Error: {}", error);
+/// }
+/// ```
+pub fn run_script(graph: &mut GFAGraph, script: &str, keep_going: bool) -> ScriptReport {
+    let mut errors = vec![];
+    let mut transaction = graph.transaction();
+    for (i, line) in script.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let applied = match line.parse::<EditOperation>() {
+            Ok(operation) => transaction.apply(operation).map(|_| ()),
+            Err(why) => Err(why),
+        };
+        let why = match applied {
+            Ok(_) => continue,
+            Err(GraphOperationError::OperationFailed(_, _, why)) => *why,
+            Err(why) => why,
+        };
+        let why = GraphOperationError::LineFailed(i + 1, Box::new(why));
+        if !keep_going {
+            errors.push(transaction.abort(why));
+            return ScriptReport {
+                batch: Batch::default(),
+                errors,
+            };
+        }
+        errors.push(why);
+    }
+    ScriptReport {
+        batch: transaction.commit(),
+        errors,
+    }
+}
+
+/// Apply an edit script read from a file
+pub fn run_script_file(
+    graph: &mut GFAGraph,
+    path: String,
+    keep_going: bool,
+) -> Result<ScriptReport, GraphOperationError> {
+    match fs::read_to_string(&path) {
+        Ok(script) => Ok(run_script(graph, &script, keep_going)),
+        Err(why) => Err(GraphOperationError::Io(path, why)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    const SCRIPT: &str = "# add a node at the end of path 14\n\
+        ADD NODE 16 GATTACA\n\
+        ADD LINK 13+ 16+\n\
+        \n\
+        REMOVE LINK 11+ 16+\n\
+        MODIFY PATH 14 11+ 12- 13+ 16+\n\
+        ADD EDGE 16+ 11+\n";
+
+    #[test]
+    fn can_keep_going_after_an_error() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        let report = run_script(&mut graph, SCRIPT, true);
+
+        assert_eq!(report.batch.operations.len(), 3);
+        let lines: Vec<String> = report.errors.iter().map(|why| why.to_string()).collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("Line 5:"));
+        assert!(lines[1].starts_with("Line 7:"));
+        assert!(graph.node_id(b"16").is_ok());
+    }
+
+    #[test]
+    fn can_stop_at_the_first_error() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        let report = run_script(&mut graph, SCRIPT, false);
+
+        assert!(!report.is_ok());
+        assert!(report.batch.operations.is_empty());
+        match &report.errors[0] {
+            GraphOperationError::LineFailed(line, _) => assert_eq!(*line, 5),
+            _ => panic!("the error should report the line"),
+        }
+        // the lines applied before the error are rolled back
+        assert!(graph.node_id(b"16").is_err());
+    }
+}
@@ -0,0 +1,138 @@
+/// parse and check the optional fields (tags) against the GFA specification
+use bstr::{BString, ByteSlice};
+use gfa2::tag::{OptField, OptFieldVal, OptionalFields};
+
+use super::error::*;
+
+// tags defined by the GFA1 and GFA2 specification with their mandatory type
+const PREDEFINED_TAGS: [(&[u8; 2], u8); 11] = [
+    (b"VN", b'Z'),
+    (b"TS", b'i'),
+    (b"LN", b'i'),
+    (b"RC", b'i'),
+    (b"FC", b'i'),
+    (b"KC", b'i'),
+    (b"SH", b'H'),
+    (b"UR", b'Z'),
+    (b"MQ", b'i'),
+    (b"NM", b'i'),
+    (b"ID", b'Z'),
+];
+
+fn invalid(field: &[u8]) -> GraphOperationError {
+    GraphOperationError::InvalidTag(field.to_str_lossy().to_string())
+}
+
+fn parse_int(value: &[u8]) -> Option<i64> {
+    value.to_str().ok()?.parse::<i64>().ok()
+}
+
+fn parse_float(value: &[u8]) -> Option<f32> {
+    value.to_str().ok()?.parse::<f32>().ok()
+}
+
+// printable characters, space included (Z and J types)
+fn is_printable(value: &[u8]) -> bool {
+    value.iter().all(|c| (b' '..=b'~').contains(c))
+}
+
+fn parse_hex(value: &[u8]) -> Option<Vec<u32>> {
+    if value.len() % 2 == 1
+        || !value
+            .iter()
+            .all(|c| c.is_ascii_digit() || (b'A'..=b'F').contains(c))
+    {
+        return None;
+    }
+    value
+        .chunks(2)
+        .map(|byte| u32::from_str_radix(byte.to_str().ok()?, 16).ok())
+        .collect()
+}
+
+fn parse_array(value: &[u8]) -> Option<OptFieldVal> {
+    let mut items = value.split_str(",");
+    let subtype = items.next()?;
+    let (min, max) = match subtype {
+        b"c" => (i8::MIN as i64, i8::MAX as i64),
+        b"C" => (u8::MIN as i64, u8::MAX as i64),
+        b"s" => (i16::MIN as i64, i16::MAX as i64),
+        b"S" => (u16::MIN as i64, u16::MAX as i64),
+        b"i" => (i32::MIN as i64, i32::MAX as i64),
+        b"I" => (u32::MIN as i64, u32::MAX as i64),
+        b"f" => {
+            return Some(OptFieldVal::BFloat(
+                items.map(parse_float).collect::<Option<_>>()?,
+            ))
+        }
+        _ => return None,
+    };
+    let items = items
+        .map(|item| parse_int(item).filter(|x| (min..=max).contains(x)))
+        .collect::<Option<Vec<i64>>>()?;
+    Some(OptFieldVal::BInt(items))
+}
+
+/// Parse an optional field written as ```TAG:TYPE:VALUE``` (for example ```KC:i:42```)
+/// checking that the name, the type and the value follow the GFA specification,
+/// and that a predefined tag (```LN```, ```KC```, ```SH```...) has the type the specification requires
+pub fn parse_tag(field: &[u8]) -> Result<OptField, GraphOperationError> {
+    let mut parts = field.splitn_str(3, ":");
+    let (tag, tag_type, value) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(tag), Some(tag_type), Some(value)) => (tag, tag_type, value),
+        _ => return Err(invalid(field)),
+    };
+    if tag.len() != 2 || !tag[0].is_ascii_alphabetic() || !tag[1].is_ascii_alphanumeric() {
+        return Err(invalid(field));
+    }
+    if tag_type.len() != 1 {
+        return Err(invalid(field));
+    }
+    let tag_type = tag_type[0];
+
+    if let Some((_, expected)) = PREDEFINED_TAGS.iter().find(|(name, _)| &name[..] == tag) {
+        if *expected != tag_type {
+            return Err(GraphOperationError::TagTypeMismatch(
+                tag.to_str_lossy().to_string(),
+                char::from(*expected).to_string(),
+            ));
+        }
+    }
+
+    let value = match tag_type {
+        b'A' if value.len() == 1 && (b'!'..=b'~').contains(&value[0]) => {
+            Some(OptFieldVal::A(value[0]))
+        }
+        b'i' => parse_int(value).map(OptFieldVal::Int),
+        b'f' => parse_float(value).map(OptFieldVal::Float),
+        b'Z' if is_printable(value) => Some(OptFieldVal::Z(BString::from(value))),
+        b'J' if is_printable(value) => Some(OptFieldVal::J(BString::from(value))),
+        b'H' => parse_hex(value).map(OptFieldVal::H),
+        b'B' => parse_array(value),
+        _ => None,
+    };
+    match value {
+        Some(value) => Ok(OptField::new(tag, value)),
+        None => Err(invalid(field)),
+    }
+}
+
+/// Find an optional field in a list of tags
+pub fn find_tag<'a>(tags: Option<&'a OptionalFields>, tag: &[u8]) -> Option<&'a OptField> {
+    tags?.iter().find(|field| &field.tag[..] == tag)
+}
+
+/// Add an optional field to a list of tags, replacing the one with the same name if it exists
+pub fn set_tag(tags: &mut OptionalFields, field: OptField) {
+    match tags.iter_mut().find(|old| old.tag == field.tag) {
+        Some(old) => *old = field,
+        None => tags.push(field),
+    }
+}
+
+/// Remove an optional field from a list of tags, returning ```false``` if it did not exist
+pub fn remove_tag(tags: &mut OptionalFields, tag: &[u8]) -> bool {
+    let len = tags.len();
+    tags.retain(|field| &field.tag[..] != tag);
+    len != tags.len()
+}
@@ -0,0 +1,314 @@
+/// apply a group of edit operations all together or not at all
+use std::mem;
+
+use super::error::*;
+use super::gfagraph::*;
+use super::operation::*;
+
+/// The operations applied by a committed ```Transaction```
+/// together with the operations that undo each of them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Batch {
+    pub operations: Vec<EditOperation>,
+    pub undo: Vec<Vec<EditOperation>>,
+}
+
+impl Batch {
+    /// The operations that undo the whole batch, in the order they must be applied
+    pub fn undo_operations(&self) -> Vec<EditOperation> {
+        self.undo.iter().rev().flatten().cloned().collect()
+    }
+}
+
+/// A group of edits on a ```GFAGraph```.\
+/// Every applied operation records the operations that undo it, so the whole group
+/// can be rolled back. A ```Transaction``` that is dropped without being committed is rolled back
+/// (use ```rollback``` or ```abort``` to know if the rollback failed)
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+/// let mut transaction = graph.transaction();
+/// transaction.apply(EditOperation::AddNode { id: "16".into(), sequence: None, length: None }).unwrap();
+/// match transaction.apply(EditOperation::AddLink { from: "16+".into(), to: "17+".into(), overlap: None }).map(|_| ()) {
+///     Ok(_) => { transaction.commit(); }
+///     Err(why) => println!("Error: {}", transaction.abort(why)),
+/// }
+/// ```
+pub struct Transaction<'a> {
+    graph: &'a mut GFAGraph,
+    operations: Vec<EditOperation>,
+    undo: Vec<Vec<EditOperation>>,
+    finished: bool,
+}
+
+impl<'a> Transaction<'a> {
+    pub fn new(graph: &'a mut GFAGraph) -> Self {
+        Transaction {
+            graph,
+            operations: vec![],
+            undo: vec![],
+            finished: false,
+        }
+    }
+
+    /// Return the graph with the operations applied so far
+    pub fn graph(&self) -> &GFAGraph {
+        self.graph
+    }
+
+    /// Apply an operation and return the operations that undo it.
+    /// If it fails the graph is left as it was before this operation
+    /// and the error reports the position of the operation in the transaction (starting from 1)
+    pub fn apply(
+        &mut self,
+        operation: EditOperation,
+    ) -> Result<&[EditOperation], GraphOperationError> {
+        match self.graph.apply(&operation) {
+            Ok(undo) => {
+                self.operations.push(operation);
+                self.undo.push(undo);
+                Ok(self.undo.last().map(|undo| undo.as_slice()).unwrap_or(&[]))
+            }
+            Err(why) => Err(GraphOperationError::OperationFailed(
+                self.operations.len() + 1,
+                operation.to_string(),
+                Box::new(why),
+            )),
+        }
+    }
+
+    /// Keep every operation applied, returning them with the operations that undo them
+    pub fn commit(mut self) -> Batch {
+        self.finished = true;
+        Batch {
+            operations: mem::take(&mut self.operations),
+            undo: mem::take(&mut self.undo),
+        }
+    }
+
+    /// Undo every operation applied, in reverse order.\
+    /// The operations that cannot be undone are skipped and their errors returned:
+    /// then the graph is not the same it was before the transaction
+    pub fn rollback(mut self) -> Result<(), Vec<GraphOperationError>> {
+        let errors = self.undo_all();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Roll back the transaction because of an error, that's returned as it is
+    /// or, when the rollback fails too, as a ```RollbackFailed``` error
+    pub fn abort(self, why: GraphOperationError) -> GraphOperationError {
+        match self.rollback() {
+            Ok(()) => why,
+            Err(errors) => GraphOperationError::RollbackFailed(Box::new(why), errors),
+        }
+    }
+
+    fn undo_all(&mut self) -> Vec<GraphOperationError> {
+        self.finished = true;
+        let mut errors = vec![];
+        while let Some(undo) = self.undo.pop() {
+            for operation in undo.iter() {
+                if let Err(why) = self.graph.apply(operation) {
+                    errors.push(GraphOperationError::OperationFailed(
+                        self.operations.len(),
+                        operation.to_string(),
+                        Box::new(why),
+                    ));
+                }
+            }
+            self.operations.pop();
+        }
+        errors
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let errors = self.undo_all();
+            debug_assert!(
+                errors.is_empty() || std::thread::panicking(),
+                "the rollback of a transaction failed: {:?}",
+                errors
+            );
+        }
+    }
+}
+
+impl GFAGraph {
+    /// Start a ```Transaction``` on the graph
+    pub fn transaction(&mut self) -> Transaction<'_> {
+        Transaction::new(self)
+    }
+
+    /// Apply a group of operations all together: if one of them fails,
+    /// the ones already applied are undone and the error reports which operation failed and why
+    /// # Example
+    /// ```ignore
+    /// use handle_gfa::graphoperation::*;
+    ///
+    /// let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+    /// let operations = vec![
+    ///     EditOperation::AddNode { id: "16".into(), sequence: Some("ACGT".into()), length: None },
+    ///     EditOperation::AddLink { from: "13+".into(), to: "16+".into(), overlap: None },
+    /// ];
+    /// graph.apply_batch(operations).unwrap();
+    /// ```
+    pub fn apply_batch(
+        &mut self,
+        operations: Vec<EditOperation>,
+    ) -> Result<Batch, GraphOperationError> {
+        let mut transaction = self.transaction();
+        for operation in operations {
+            if let Err(why) = transaction.apply(operation).map(|_| ()) {
+                return Err(transaction.abort(why));
+            }
+        }
+        Ok(transaction.commit())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    fn snapshot(graph: &GFAGraph) -> String {
+        use handlegraph2::handlegraph::HandleGraph;
+
+        let mut nodes: Vec<String> = graph
+            .graph
+            .handles_iter()
+            .map(|handle| format!("{:?}", graph.oriented_id(handle)))
+            .collect();
+        nodes.sort();
+        let mut edges: Vec<String> = graph
+            .graph
+            .edges_iter()
+            .map(|edge| format!("{:?}", edge_key(edge)))
+            .collect();
+        edges.sort();
+        let mut paths: Vec<String> = graph
+            .graph
+            .paths
+            .values()
+            .map(|path| format!("{:?}", path.nodes))
+            .collect();
+        paths.sort();
+        format!("{:?} {:?} {:?}", nodes, edges, paths)
+    }
+
+    #[test]
+    fn can_commit_a_batch() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        let batch = graph
+            .apply_batch(vec![
+                EditOperation::AddNode {
+                    id: "16".into(),
+                    sequence: Some("GATTACA".into()),
+                    length: None,
+                },
+                EditOperation::AddLink {
+                    from: "13+".into(),
+                    to: "16+".into(),
+                    overlap: None,
+                },
+                EditOperation::ModifyPath {
+                    name: "14".into(),
+                    steps: vec!["11+".into(), "13+".into(), "16+".into()],
+                },
+            ])
+            .unwrap();
+        assert_eq!(batch.operations.len(), 3);
+        assert!(graph.node_id(b"16").is_ok());
+        print_simple_graph(&graph.graph);
+    }
+
+    #[test]
+    fn can_rollback_a_failed_batch() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        graph.set_node_tag(b"11", b"KC:i:42").unwrap();
+        let before = snapshot(&graph);
+
+        let result = graph.apply_batch(vec![
+            EditOperation::AddNode {
+                id: "16".into(),
+                sequence: None,
+                length: None,
+            },
+            EditOperation::AddLink {
+                from: "13+".into(),
+                to: "16+".into(),
+                overlap: None,
+            },
+            EditOperation::RemoveNode { id: "11".into() },
+            EditOperation::ModifyLink {
+                from: "12-".into(),
+                to: "13+".into(),
+                new_from: Some("16-".into()),
+                new_to: None,
+                overlap: None,
+            },
+            EditOperation::AddLink {
+                from: "16+".into(),
+                to: "missing+".into(),
+                overlap: None,
+            },
+        ]);
+        match result {
+            Err(GraphOperationError::OperationFailed(5, operation, _)) => {
+                assert_eq!(operation, "ADD LINK 16+ missing+")
+            }
+            _ => panic!("the fifth operation should fail"),
+        }
+        assert_eq!(before, snapshot(&graph));
+        assert!(graph.node_id(b"16").is_err());
+        assert_eq!(
+            get_node_tag(&graph, b"11", b"KC").unwrap().to_string(),
+            "KC:i:42"
+        );
+    }
+    #[test]
+    fn can_report_the_failures_of_a_rollback() {
+        let mut graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        let mut transaction = graph.transaction();
+        transaction
+            .apply(EditOperation::AddNode {
+                id: "16".into(),
+                sequence: None,
+                length: None,
+            })
+            .unwrap();
+        // an undo operation that cannot be applied
+        transaction
+            .operations
+            .push(EditOperation::RemoveNode { id: "17".into() });
+        transaction.undo.push(vec![EditOperation::RemoveNode {
+            id: "missing".into(),
+        }]);
+
+        let why = transaction.abort(GraphOperationError::LineFailed(
+            3,
+            Box::new(GraphOperationError::EmptyId),
+        ));
+        match &why {
+            GraphOperationError::RollbackFailed(cause, errors) => {
+                assert!(matches!(**cause, GraphOperationError::LineFailed(3, _)));
+                assert_eq!(errors.len(), 1);
+                assert!(matches!(
+                    errors[0],
+                    GraphOperationError::OperationFailed(2, _, _)
+                ));
+            }
+            _ => panic!("the rollback should fail"),
+        }
+        assert!(why.to_string().contains("the graph cannot be restored"));
+        // the operations that can be undone are undone anyway
+        assert!(graph.node_id(b"16").is_err());
+    }
+}
@@ -0,0 +1,232 @@
+/// check that a graph is coherent: every edge and every step refers to an existing node,
+/// every path follows the edges of the graph and the path names are unique
+use bstr::BString;
+use handlegraph2::{
+    handle::{Direction, Edge, Handle, NodeId},
+    handlegraph::HandleGraph,
+    hashgraph::HashGraph,
+};
+use std::{collections::BTreeMap, fmt};
+
+/// A problem found in a graph by ```validate```
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphIssue {
+    /// an edge whose end is a node that does not exist
+    DanglingEdge { edge: Edge, missing: NodeId },
+    /// 2 consecutive steps of a path (from ```step``` to the next one) not joined by an edge
+    MissingPathEdge {
+        path: BString,
+        step: usize,
+        edge: Edge,
+    },
+    /// a step of a path on a node that does not exist
+    OrphanStep {
+        path: BString,
+        step: usize,
+        node: NodeId,
+    },
+    /// more than one path with the same name
+    DuplicatePath { path: BString, count: usize },
+}
+
+fn oriented(handle: Handle, name: &impl Fn(NodeId) -> String) -> String {
+    let orientation = if handle.is_reverse() { "-" } else { "+" };
+    format!("{}{}", name(handle.id()), orientation)
+}
+
+impl GraphIssue {
+    /// Describe the problem naming the nodes with ```name```
+    /// (```GFAGraph::node_name``` gives the names of the segments they have been read from)
+    pub fn describe(&self, name: impl Fn(NodeId) -> String) -> String {
+        match self {
+            GraphIssue::DanglingEdge {
+                edge: Edge(left, right),
+                missing,
+            } => format!(
+                "The edge ({} -> {}) refers to a node that does not exist ({})",
+                oriented(*left, &name),
+                oriented(*right, &name),
+                name(*missing)
+            ),
+            GraphIssue::MissingPathEdge {
+                path,
+                step,
+                edge: Edge(left, right),
+            } => format!(
+                "The steps {} and {} of the path ({}) are not joined by an edge ({} -> {})",
+                step,
+                step + 1,
+                path,
+                oriented(*left, &name),
+                oriented(*right, &name)
+            ),
+            GraphIssue::OrphanStep { path, step, node } => format!(
+                "The step {} of the path ({}) refers to a node that does not exist ({})",
+                step,
+                path,
+                name(*node)
+            ),
+            GraphIssue::DuplicatePath { path, count } => {
+                format!("The path name ({}) is used by {} paths", path, count)
+            }
+        }
+    }
+}
+
+impl fmt::Display for GraphIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe(|id| id.to_string()))
+    }
+}
+
+/// The problems found in a graph, in the order of the node ids and of the paths
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    pub issues: Vec<GraphIssue>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Check that a graph is coherent, reporting the edges to nodes that do not exist,
+/// the steps on nodes that do not exist, the consecutive steps of a path not joined by an edge
+/// (a circular path ends with its first step, so it's closed by its last 2 steps)
+/// and the path names used more than once.\
+/// The steps are numbered from 0
+/// # Example
+/// ```ignore
+/// use handle_gfa::graphoperation::*;
+///
+/// let graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+/// let report = validate(&graph.graph);
+/// for issue in report.issues.iter() {
+///     println!("{}", issue.describe(|id| graph.node_name(id).to_string()));
+/// }
+/// ```
+pub fn validate(graph: &HashGraph) -> ValidationReport {
+    let mut issues = vec![];
+
+    let mut nodes: Vec<&NodeId> = graph.graph.keys().collect();
+    nodes.sort();
+    for id in nodes {
+        let node = &graph.graph[id];
+        let handle = Handle::pack(*id, false);
+        let edges = node
+            .left_edges
+            .iter()
+            .map(|left| (Edge(*left, handle), *left))
+            .chain(
+                node.right_edges
+                    .iter()
+                    .map(|right| (Edge(handle, *right), *right)),
+            );
+        for (edge, other) in edges {
+            if !graph.graph.contains_key(&other.id()) {
+                issues.push(GraphIssue::DanglingEdge {
+                    edge,
+                    missing: other.id(),
+                });
+            }
+        }
+    }
+
+    let mut paths: Vec<_> = graph.paths.iter().collect();
+    paths.sort_by_key(|(id, _)| **id);
+    let mut names: BTreeMap<&[u8], usize> = BTreeMap::new();
+    for (_, path) in paths {
+        *names.entry(&path.name).or_insert(0) += 1;
+        let name = BString::from(path.name.as_slice());
+        for (step, handle) in path.nodes.iter().enumerate() {
+            if !graph.graph.contains_key(&handle.id()) {
+                issues.push(GraphIssue::OrphanStep {
+                    path: name.clone(),
+                    step,
+                    node: handle.id(),
+                });
+            }
+        }
+        for (step, pair) in path.nodes.windows(2).enumerate() {
+            let (left, right) = (&pair[0], &pair[1]);
+            // the steps on nodes that do not exist are already reported
+            if !graph.graph.contains_key(&left.id()) || !graph.graph.contains_key(&right.id()) {
+                continue;
+            }
+            if !graph
+                .handle_edges_iter(*left, Direction::Right)
+                .any(|next| next == *right)
+            {
+                issues.push(GraphIssue::MissingPathEdge {
+                    path: name.clone(),
+                    step,
+                    edge: Edge(*left, *right),
+                });
+            }
+        }
+    }
+    for (name, count) in names {
+        if count > 1 {
+            issues.push(GraphIssue::DuplicatePath {
+                path: BString::from(name),
+                count,
+            });
+        }
+    }
+
+    ValidationReport { issues }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use handlegraph2::{
+        handle::{Handle, NodeId},
+        pathgraph::PathHandleGraph,
+    };
+
+    #[test]
+    fn can_validate_a_graph() {
+        let gfa1 = b"H\tVN:Z:1.0\nS\t11\tACCTT\nS\t12\tTCAAGG\nS\t13\tCTTGATT\n\
+            L\t11\t+\t12\t-\t0M\nL\t12\t-\t13\t+\t0M\nP\t14\t11+,12-,13+\t*\n";
+        let (_, mut graph) = load_graph_from_reader(&gfa1[..]).unwrap();
+        assert!(validate(&graph.graph).is_valid());
+        // a circular path ends with its first step
+        graph.add_link_between_nodes(b"13+", b"11+", None).unwrap();
+        graph
+            .add_path(Some(b"16"), vec![b"11+", b"12-", b"13+", b"11+"])
+            .unwrap();
+        assert!(validate(&graph.graph).is_valid());
+        graph.remove_path(Some(b"16")).unwrap();
+        graph.remove_link(b"13+", b"11+").unwrap();
+
+        graph
+            .add_path_with_links(b"15", vec![b"11+", b"13+"], PathLinks::Unchecked)
+            .unwrap();
+        let id = graph.node_id(b"12").unwrap();
+        graph.graph.graph.remove(&id);
+        let path = graph.graph.create_path_handle(b"14", false);
+        graph.graph.append_step(&path, Handle::pack(id, false));
+
+        let report = validate(&graph.graph);
+        let name = |id: NodeId| graph.node_name(id).to_string();
+        let issues: Vec<String> = report
+            .issues
+            .iter()
+            .map(|issue| issue.describe(name))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                "The edge (11+ -> 12-) refers to a node that does not exist (12)",
+                "The edge (12- -> 13+) refers to a node that does not exist (12)",
+                "The step 1 of the path (14) refers to a node that does not exist (12)",
+                "The steps 0 and 1 of the path (15) are not joined by an edge (11+ -> 13+)",
+                "The step 0 of the path (14) refers to a node that does not exist (12)",
+                "The path name (14) is used by 2 paths",
+            ]
+        );
+        assert!(!report.is_valid());
+    }
+}
@@ -0,0 +1,124 @@
+/// tell the version of the GFA format of a file from its content (or its extension)
+use bstr::ByteSlice;
+use std::{
+    fmt,
+    io::{self, BufRead},
+    path::Path,
+    str::FromStr,
+};
+
+use super::error::*;
+
+/// The version of the GFA format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GFAVersion {
+    GFA1,
+    GFA2,
+}
+
+impl fmt::Display for GFAVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GFAVersion::GFA1 => write!(f, "GFA1"),
+            GFAVersion::GFA2 => write!(f, "GFA2"),
+        }
+    }
+}
+
+/// Read the name of a version (```gfa1``` or ```gfa2```, case insensitive)
+impl FromStr for GFAVersion {
+    type Err = GraphOperationError;
+
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        match version.to_ascii_uppercase().as_str() {
+            "GFA" | "GFA1" | "1.0" => Ok(GFAVersion::GFA1),
+            "GFA2" | "2.0" => Ok(GFAVersion::GFA2),
+            _ => Err(GraphOperationError::InvalidVersion(version.to_string())),
+        }
+    }
+}
+
+impl GFAVersion {
+    /// The version given by the extension of a file (```.gfa``` or ```.gfa2```),
+    /// looking behind the ```.gz``` of the compressed ones
+    pub fn from_extension<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let path = path.as_ref();
+        let path = match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("gz") => Path::new(path.file_stem()?),
+            _ => path,
+        };
+        match path.extension()?.to_str()?.to_ascii_uppercase().as_str() {
+            "GFA" => Some(GFAVersion::GFA1),
+            "GFA2" => Some(GFAVersion::GFA2),
+            _ => None,
+        }
+    }
+
+    /// The version told by a line: the ```VN``` tag of the header,
+    /// or a record type that exists only in one version (```E F G O U``` or ```L C P W```)
+    pub fn from_line(line: &[u8]) -> Option<Self> {
+        let mut fields = line.split_str("\t");
+        match fields.next()? {
+            b"H" => fields.find_map(|field| {
+                if field.starts_with(b"VN:Z:1") {
+                    Some(GFAVersion::GFA1)
+                } else if field.starts_with(b"VN:Z:2") {
+                    Some(GFAVersion::GFA2)
+                } else {
+                    None
+                }
+            }),
+            b"E" | b"F" | b"G" | b"O" | b"U" => Some(GFAVersion::GFA2),
+            b"L" | b"C" | b"P" | b"W" => Some(GFAVersion::GFA1),
+            _ => None,
+        }
+    }
+}
+
+/// Read the lines until one of them tells the version of the GFA format,
+/// keeping every byte read in ```consumed``` so they can be parsed again
+pub fn detect_version<R: BufRead>(
+    reader: &mut R,
+    consumed: &mut Vec<u8>,
+) -> io::Result<Option<GFAVersion>> {
+    loop {
+        let start = consumed.len();
+        if reader.read_until(b'\n', consumed)? == 0 {
+            return Ok(None);
+        }
+        let line = consumed[start..].trim_end_with(|c| c == '\n' || c == '\r');
+        if let Some(version) = GFAVersion::from_line(line) {
+            return Ok(Some(version));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_detect_the_version() {
+        let gfa1 = b"S\t11\tACCTT\nS\t12\tTCAAGG\nL\t11\t+\t12\t-\t0M\nS\t13\tC\n";
+        let mut consumed = vec![];
+        let version = detect_version(&mut &gfa1[..], &mut consumed).unwrap();
+        assert_eq!(version, Some(GFAVersion::GFA1));
+        assert_eq!(consumed, &gfa1[..38]);
+
+        let header = &b"H\tVN:Z:2.0\n"[..];
+        let version = detect_version(&mut &header[..], &mut vec![]).unwrap();
+        assert_eq!(version, Some(GFAVersion::GFA2));
+        let segments = &b"S\t11\tACCTT\n"[..];
+        assert_eq!(
+            detect_version(&mut &segments[..], &mut vec![]).unwrap(),
+            None
+        );
+
+        assert_eq!(
+            GFAVersion::from_extension("graph.gfa2.gz"),
+            Some(GFAVersion::GFA2)
+        );
+        assert_eq!(GFAVersion::from_extension("graph.txt"), None);
+        assert_eq!("gfa1".parse::<GFAVersion>().unwrap(), GFAVersion::GFA1);
+    }
+}
@@ -0,0 +1,296 @@
+/// keep the haplotype of the paths read from the walks (W records) of GFA 1.1
+use bstr::{BString, ByteSlice};
+use gfa2::tag::OptionalFields;
+
+use super::error::*;
+use super::gfagraph::*;
+use super::tag::*;
+
+/// The haplotype a path comes from, as told by a walk (```W``` record of ```GFA 1.1```)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Walk {
+    pub sample: BString,
+    /// the index of the haplotype (0 for a haploid sample)
+    pub haplotype: usize,
+    pub seq_id: BString,
+    /// the range of the sequence covered by the walk (```*``` when not known)
+    pub start: Option<usize>,
+    pub end: Option<usize>,
+}
+
+fn optional_position(position: &[u8]) -> Result<Option<usize>, GraphOperationError> {
+    if position == b"*" {
+        return Ok(None);
+    }
+    match position
+        .to_str()
+        .ok()
+        .and_then(|position| position.parse().ok())
+    {
+        Some(position) => Ok(Some(position)),
+        None => Err(GraphOperationError::InvalidWalk(format!(
+            "The position ({}) is not a number",
+            position.as_bstr()
+        ))),
+    }
+}
+
+fn or_star(position: Option<usize>) -> String {
+    match position {
+        Some(position) => position.to_string(),
+        None => "*".to_string(),
+    }
+}
+
+impl Walk {
+    /// Read the sample, the haplotype index, the sequence id and the range of a walk
+    pub fn new(
+        sample: &[u8],
+        haplotype: &[u8],
+        seq_id: &[u8],
+        start: &[u8],
+        end: &[u8],
+    ) -> Result<Self, GraphOperationError> {
+        let haplotype = match optional_position(haplotype)? {
+            Some(haplotype) => haplotype,
+            None => {
+                return Err(GraphOperationError::InvalidWalk(
+                    "The haplotype index is required".to_string(),
+                ))
+            }
+        };
+        Ok(Walk {
+            sample: BString::from(sample),
+            haplotype,
+            seq_id: BString::from(seq_id),
+            start: optional_position(start)?,
+            end: optional_position(end)?,
+        })
+    }
+
+    /// The fields of the record before the steps, without the record type
+    pub fn fields(&self) -> Vec<String> {
+        vec![
+            self.sample.to_string(),
+            self.haplotype.to_string(),
+            self.seq_id.to_string(),
+            or_star(self.start),
+            or_star(self.end),
+        ]
+    }
+
+    /// The name of the path the walk is stored as, following the PanSN convention
+    /// (```sample#haplotype#seq_id```), with the range (```[start-end]```)
+    /// only when the walk does not start from the beginning of the sequence
+    pub fn path_name(&self) -> BString {
+        let mut name = format!("{}#{}#{}", self.sample, self.haplotype, self.seq_id);
+        if let (Some(start), Some(end)) = (self.start, self.end) {
+            if start != 0 {
+                name.push_str(&format!("[{}-{}]", start, end));
+            }
+        }
+        BString::from(name)
+    }
+
+    /// The walk told by the name of a path that follows the PanSN convention,
+    /// ```None``` for the other names
+    pub fn from_path_name(name: &[u8]) -> Option<Self> {
+        let fields: Vec<&[u8]> = name.split_str("#").collect();
+        let (sample, haplotype, seq_id) = match fields[..] {
+            [sample, haplotype, seq_id] if !sample.is_empty() && !seq_id.is_empty() => {
+                (sample, haplotype.to_str().ok()?.parse().ok()?, seq_id)
+            }
+            _ => return None,
+        };
+        let (seq_id, start, end) = match (seq_id.find_byte(b'['), seq_id.last()) {
+            (Some(open), Some(b']')) => {
+                let range = seq_id[open + 1..seq_id.len() - 1].to_str().ok()?;
+                let (start, end) = range.split_once('-')?;
+                (
+                    &seq_id[..open],
+                    Some(start.parse().ok()?),
+                    Some(end.parse().ok()?),
+                )
+            }
+            _ => (seq_id, None, None),
+        };
+        Some(Walk {
+            sample: BString::from(sample),
+            haplotype,
+            seq_id: BString::from(seq_id),
+            start,
+            end,
+        })
+    }
+}
+
+/// A walk line (```W sample haplotype seq_id start end >11<12>13 [tags]```)
+/// with its steps written as segment references with orientation (```11+ 12- 13+```)
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalkRecord {
+    pub walk: Walk,
+    pub steps: Vec<BString>,
+    pub tags: OptionalFields,
+}
+
+impl WalkRecord {
+    /// Parse a ```W``` line
+    pub fn parse(line: &[u8]) -> Result<Self, GraphOperationError> {
+        let fields: Vec<&[u8]> = line.split_str("\t").collect();
+        match &fields[..] {
+            [b"W", sample, haplotype, seq_id, start, end, steps, tags @ ..] => Ok(WalkRecord {
+                walk: Walk::new(sample, haplotype, seq_id, start, end)?,
+                steps: walk_steps(steps)?,
+                tags: tags
+                    .iter()
+                    .map(|tag| parse_tag(tag))
+                    .collect::<Result<OptionalFields, GraphOperationError>>()?,
+            }),
+            _ => Err(GraphOperationError::InvalidWalk(
+                "A walk needs 7 fields".to_string(),
+            )),
+        }
+    }
+}
+
+/// Read the steps of a walk (```>11<12>13```) as segment references with orientation (```11+ 12- 13+```)
+pub fn walk_steps(walk: &[u8]) -> Result<Vec<BString>, GraphOperationError> {
+    if walk == b"*" {
+        return Ok(vec![]);
+    }
+    if !matches!(walk.first(), Some(b'>') | Some(b'<')) {
+        return Err(GraphOperationError::InvalidWalk(format!(
+            "The walk ({}) must start with > or <",
+            walk.as_bstr()
+        )));
+    }
+    let mut steps = vec![];
+    let mut rest = walk;
+    while let Some((orientation, after)) = rest.split_first() {
+        let len = after
+            .iter()
+            .position(|c| matches!(c, b'>' | b'<'))
+            .unwrap_or(after.len());
+        if len == 0 {
+            return Err(GraphOperationError::EmptyId);
+        }
+        let mut step = BString::from(&after[..len]);
+        step.push(if *orientation == b'<' { b'-' } else { b'+' });
+        steps.push(step);
+        rest = &after[len..];
+    }
+    Ok(steps)
+}
+
+/// The haplotype of a path can be set or removed like any other element of the graph:
+/// a path without it is written as a ```P``` record, otherwise as a ```W``` record
+impl GFAGraph {
+    /// Set the haplotype an existing path comes from
+    pub fn set_walk(&mut self, path_name: &[u8], walk: Walk) -> Result<(), GraphOperationError> {
+        if !self.has_path(path_name) {
+            return Err(GraphOperationError::PathNotExist(
+                path_name.to_str_lossy().to_string(),
+            ));
+        }
+        self.walks.insert(BString::from(path_name), walk);
+        Ok(())
+    }
+
+    /// Remove the haplotype of a path, that's written again as a ```P``` record
+    pub fn remove_walk(&mut self, path_name: &[u8]) -> Result<(), GraphOperationError> {
+        match self.walks.remove(path_name.as_bstr()) {
+            Some(_) => Ok(()),
+            None => Err(GraphOperationError::RecordNotExist(format!(
+                "W {}",
+                path_name.as_bstr()
+            ))),
+        }
+    }
+
+    /// Return the haplotype a path comes from, if it has been read from a walk or set later
+    pub fn walk(&self, path_name: &[u8]) -> Option<&Walk> {
+        self.walks.get(path_name.as_bstr())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_read_walks() {
+        let record = WalkRecord::parse(b"W\tNA12878\t1\tchr1\t10\t25\t>11<12>13\txx:i:1").unwrap();
+        assert_eq!(record.steps, ["11+", "12-", "13+"]);
+        assert_eq!(record.walk.path_name(), "NA12878#1#chr1[10-25]");
+        assert_eq!(record.tags.len(), 1);
+        assert_eq!(
+            Walk::from_path_name(b"NA12878#1#chr1[10-25]"),
+            Some(record.walk)
+        );
+
+        let walk = Walk::from_path_name(b"HG002#2#chr2").unwrap();
+        assert_eq!(walk.fields(), ["HG002", "2", "chr2", "*", "*"]);
+        assert_eq!(Walk::from_path_name(b"path_1"), None);
+        assert!(walk_steps(b"11>12").is_err());
+        assert!(WalkRecord::parse(b"W\tNA12878\t*\tchr1\t0\t5\t>11").is_err());
+    }
+
+    #[test]
+    fn can_keep_walks_and_containments() {
+        use super::super::*;
+        use crate::fileoperation::*;
+
+        let gfa1 = b"H\tVN:Z:1.1\nS\t11\tACCTT\nS\t12\tTC\nS\t13\tGG\n\
+            L\t11\t+\t12\t-\t0M\nC\t11\t+\t13\t-\t2\t2M\n\
+            P\tref#0#chr1\t11+,12-\t*\nW\tNA12878\t1\tchr1\t0\t7\t>11<12\n";
+        let (version, mut graph) = load_graph_from_reader(&gfa1[..]).unwrap();
+        assert_eq!(version, GFAVersion::GFA1);
+        assert_eq!(graph.containments[0].container, "11+");
+        assert_eq!(graph.walk(b"NA12878#1#chr1").unwrap().end, Some(7));
+
+        let save = |graph: &GFAGraph, path_lines: PathLines| {
+            let options = SaveOptions {
+                path_lines,
+                ..SaveOptions::default()
+            };
+            let mut saved = vec![];
+            save_as_gfa1_to_writer_with_options(graph, &mut saved, &options).unwrap();
+            String::from_utf8(saved).unwrap()
+        };
+        let saved = save(&graph, PathLines::Input);
+        assert!(saved.contains("C\t11\t+\t13\t-\t2\t2M\n"));
+        assert!(saved.contains("P\tref#0#chr1\t11+,12-\t*\n"));
+        assert!(saved.contains("W\tNA12878\t1\tchr1\t0\t7\t>11<12\n"));
+        let saved = save(&graph, PathLines::Walks);
+        assert!(saved.contains("W\tref\t0\tchr1\t0\t7\t>11<12\n"));
+        let saved = save(&graph, PathLines::Paths);
+        assert!(saved.contains("P\tNA12878#1#chr1\t11+,12-\t*\n"));
+
+        let mut history = History::new();
+        history
+            .apply(&mut graph, EditOperation::RemoveNode { id: "13".into() })
+            .unwrap();
+        assert!(graph.containments.is_empty());
+        history
+            .apply(
+                &mut graph,
+                EditOperation::RemovePath {
+                    name: "NA12878#1#chr1".into(),
+                },
+            )
+            .unwrap();
+        assert!(graph.walks.is_empty());
+        history.undo(&mut graph).unwrap();
+        history.undo(&mut graph).unwrap();
+        assert_eq!(graph.containments.len(), 1);
+        assert_eq!(graph.walk(b"NA12878#1#chr1").unwrap().sample, "NA12878");
+
+        let operation: EditOperation = "SET WALK ref#0#chr1 ref 0 chr1 * *".parse().unwrap();
+        assert_eq!(operation.to_string(), "SET WALK ref#0#chr1 ref 0 chr1 * *");
+        let undo = graph.apply(&operation).unwrap();
+        assert_eq!(undo[0].to_string(), "REMOVE WALK ref#0#chr1");
+        assert!(graph
+            .apply(&"ADD CONTAINMENT 11+ 14+ 0 *".parse().unwrap())
+            .is_err());
+    }
+}
@@ -0,0 +1,510 @@
+use handlegfa::fileoperation::*;
+use handlegfa::graphoperation::*;
+
+mod repl;
+
+#[macro_use]
+extern crate clap;
+use bstr::BString;
+use clap::ArgMatches;
+use std::{
+    fs::File,
+    io::{self, Read},
+    process,
+};
+
+// the exit code of every kind of GraphOperationError,
+// the arguments that are not valid are reported by clap with its own exit code
+const EXIT_UNSUPPORTED: i32 = 1;
+const EXIT_FILE: i32 = 3;
+const EXIT_INVALID_INPUT: i32 = 4;
+const EXIT_NOT_FOUND: i32 = 5;
+const EXIT_ALREADY_EXIST: i32 = 6;
+const EXIT_CONFLICT: i32 = 7;
+const EXIT_INVALID_GRAPH: i32 = 8;
+
+fn exit_code(error: &GraphOperationError) -> i32 {
+    use GraphOperationError as GE;
+    match error {
+        GE::Io(_, _) | GE::Parse { .. } | GE::UnknownVersion => EXIT_FILE,
+        GE::InvalidOrientedId(_)
+        | GE::EmptyId
+        | GE::InvalidId(_)
+        | GE::InvalidSequence(_)
+        | GE::InvalidWalk(_)
+        | GE::InvalidTag(_)
+        | GE::InvalidOverlap(_)
+        | GE::TagTypeMismatch(_, _)
+        | GE::InvalidOperation(_)
+        | GE::AmbiguousRecord(_)
+        | GE::InvalidPatch(_, _)
+        | GE::InvalidVersion(_) => EXIT_INVALID_INPUT,
+        GE::NodesNotExist(_, _)
+        | GE::EdgeNotExist(_, _)
+        | GE::PathNotExist(_)
+        | GE::RecordNotExist(_)
+        | GE::TagNotExist(_, _) => EXIT_NOT_FOUND,
+        GE::IdAlreadyExist(_) | GE::DuplicatePath(_) => EXIT_ALREADY_EXIST,
+        GE::PatchConflict(_, _, _) | GE::NodeInPaths(_, _) => EXIT_CONFLICT,
+        GE::OperationFailed(_, _, why) | GE::LineFailed(_, why) | GE::RollbackFailed(why, _) => {
+            exit_code(why)
+        }
+        GE::NotSupported(_) => EXIT_UNSUPPORTED,
+        GE::InvalidGraph(_) => EXIT_INVALID_GRAPH,
+    }
+}
+
+// print every error and exit with the code of the first one
+fn exit_on_errors(errors: &[GraphOperationError]) {
+    for why in errors.iter() {
+        eprintln!("Error: {}", why);
+    }
+    if let Some(why) = errors.first() {
+        process::exit(exit_code(why));
+    }
+}
+
+// the name that stands for the standard input or output
+const STDIO: &str = "-";
+
+// the version of the format to write in a file: the one given by its extension,
+// the standard output uses the one given with --format, otherwise the one of the input file
+fn file_version(file: &str, format: Option<GFAVersion>, input: GFAVersion) -> GFAVersion {
+    match file {
+        STDIO => format.unwrap_or(input),
+        _ => GFAVersion::from_extension(file).or(format).unwrap_or(input),
+    }
+}
+
+// print the records skipped or repaired while reading a graph in lenient mode
+fn print_warnings(
+    (version, graph, warnings): (GFAVersion, GFAGraph, Vec<LoadWarning>),
+) -> (GFAVersion, GFAGraph) {
+    for warning in warnings.iter() {
+        eprintln!("Warning: {}", warning);
+    }
+    (version, graph)
+}
+
+// read a graph telling its version from the content of the file,
+// the version of the standard input can be given with --format.
+// With --lenient the records that cannot be loaded are skipped (or repaired) with a warning
+fn load(
+    file: &str,
+    format: Option<GFAVersion>,
+    lenient: bool,
+) -> Result<(GFAVersion, GFAGraph), GraphOperationError> {
+    if lenient {
+        let loaded = match (file, format) {
+            (STDIO, Some(GFAVersion::GFA1)) => {
+                let (graph, warnings) = gfa1_to_handlegraph_from_reader_lenient(io::stdin())?;
+                (GFAVersion::GFA1, graph, warnings)
+            }
+            (STDIO, Some(GFAVersion::GFA2)) => {
+                let (graph, warnings) = gfa2_to_handlegraph_from_reader_lenient(io::stdin())?;
+                (GFAVersion::GFA2, graph, warnings)
+            }
+            (STDIO, None) => load_graph_from_reader_lenient(io::stdin())?,
+            _ => load_graph_lenient(file.to_string())?,
+        };
+        return Ok(print_warnings(loaded));
+    }
+    match (file, format) {
+        (STDIO, Some(GFAVersion::GFA1)) => Ok((
+            GFAVersion::GFA1,
+            gfa1_to_handlegraph_from_reader(io::stdin())?,
+        )),
+        (STDIO, Some(GFAVersion::GFA2)) => Ok((
+            GFAVersion::GFA2,
+            gfa2_to_handlegraph_from_reader(io::stdin())?,
+        )),
+        (STDIO, None) => load_graph_from_reader(io::stdin()),
+        _ => load_graph(file.to_string()),
+    }
+}
+
+fn save(
+    graph: &GFAGraph,
+    file: &str,
+    version: GFAVersion,
+    options: &SaveOptions,
+) -> Result<(), GraphOperationError> {
+    match (file, version) {
+        (STDIO, GFAVersion::GFA1) => {
+            save_as_gfa1_to_writer_with_options(graph, io::stdout().lock(), options)
+        }
+        (STDIO, GFAVersion::GFA2) => {
+            save_as_gfa2_to_writer_with_options(graph, io::stdout().lock(), options)
+        }
+        (_, GFAVersion::GFA1) => {
+            save_as_gfa1_file_with_options(graph, Some(file.to_string()), options)
+        }
+        (_, GFAVersion::GFA2) => {
+            save_as_gfa2_file_with_options(graph, Some(file.to_string()), options)
+        }
+    }
+}
+
+fn value(matches: &ArgMatches, name: &str) -> BString {
+    BString::from(matches.value_of(name).unwrap_or_default())
+}
+
+fn values(matches: &ArgMatches, name: &str) -> Vec<BString> {
+    matches
+        .values_of(name)
+        .map(|values| values.map(BString::from).collect())
+        .unwrap_or_default()
+}
+
+// the edit described by the arguments of a subcommand
+fn edit_operation(command: &str, matches: &ArgMatches) -> Option<EditOperation> {
+    use EditOperation as EO;
+    let operation = match command {
+        "add-node" => EO::AddNode {
+            id: value(matches, "ID"),
+            sequence: matches.value_of("SEQUENCE").map(BString::from),
+            length: None,
+        },
+        "add-link" => EO::AddLink {
+            from: value(matches, "FROM"),
+            to: value(matches, "TO"),
+            overlap: matches.value_of("OVERLAP").map(BString::from),
+        },
+        "add-path" => EO::AddPath {
+            name: value(matches, "NAME"),
+            steps: values(matches, "STEPS"),
+            // the steps must be joined by the links of the graph, unless they are created
+            links: if matches.is_present("CREATE_LINKS") {
+                PathLinks::Create
+            } else {
+                PathLinks::Strict
+            },
+        },
+        "remove-node" => EO::RemoveNode {
+            id: value(matches, "ID"),
+        },
+        "remove-link" => EO::RemoveLink {
+            from: value(matches, "FROM"),
+            to: value(matches, "TO"),
+        },
+        "remove-path" => EO::RemovePath {
+            name: value(matches, "NAME"),
+        },
+        "modify-node" => EO::ModifyNode {
+            id: value(matches, "ID"),
+            sequence: value(matches, "SEQUENCE"),
+        },
+        "modify-link" => EO::ModifyLink {
+            from: value(matches, "FROM"),
+            to: value(matches, "TO"),
+            new_from: matches.value_of("NEW_FROM").map(BString::from),
+            new_to: matches.value_of("NEW_TO").map(BString::from),
+            overlap: matches.value_of("OVERLAP").map(BString::from),
+        },
+        "modify-path" => EO::ModifyPath {
+            name: value(matches, "NAME"),
+            steps: values(matches, "STEPS"),
+        },
+        _ => return None,
+    };
+    Some(operation)
+}
+
+// convert the input into the other version of the format (or the one given by the output),
+// printing on the standard error the records that cannot be represented in the output
+fn run_convert(
+    input: &str,
+    output: &str,
+    format: Option<GFAVersion>,
+    lenient: bool,
+    options: &SaveOptions,
+) -> Result<(), GraphOperationError> {
+    let file_error = |why: io::Error| GraphOperationError::Io(input.to_string(), why);
+    // the standard input is kept in memory to be read twice
+    let stdin = match input {
+        STDIO => {
+            let mut data = vec![];
+            io::stdin().read_to_end(&mut data).map_err(file_error)?;
+            Some(data)
+        }
+        _ => None,
+    };
+    let (from, graph) = match (&stdin, lenient) {
+        (Some(data), true) => print_warnings(load_graph_from_reader_lenient(&data[..])?),
+        (Some(data), false) => load_graph_from_reader(&data[..])?,
+        (None, true) => print_warnings(load_graph_lenient(input.to_string())?),
+        (None, false) => load_graph(input.to_string())?,
+    };
+    let other = match from {
+        GFAVersion::GFA1 => GFAVersion::GFA2,
+        GFAVersion::GFA2 => GFAVersion::GFA1,
+    };
+    let to = match output {
+        STDIO => format,
+        _ => GFAVersion::from_extension(output).or(format),
+    }
+    .unwrap_or(other);
+    let report = match &stdin {
+        Some(data) => conversion_report(&data[..], from, to)?,
+        None => conversion_report(File::open(input).map_err(file_error)?, from, to)?,
+    };
+
+    save(&graph, output, to, options)?;
+    for lost in report.lost.iter() {
+        eprintln!("Warning: {}", lost);
+    }
+    if !report.is_lossless() {
+        eprintln!(
+            "{} records of the {} file cannot be represented in the {} file",
+            report.lost.len(),
+            from,
+            to
+        );
+    }
+    Ok(())
+}
+
+// print every problem of the graph, that's never saved
+fn run_validate(graph: &GFAGraph) -> Result<(), GraphOperationError> {
+    let report = validate(&graph.graph);
+    for issue in report.issues.iter() {
+        println!("{}", issue.describe(|id| graph.node_name(id).to_string()));
+    }
+    if report.is_valid() {
+        Ok(())
+    } else {
+        Err(GraphOperationError::InvalidGraph(report.issues.len()))
+    }
+}
+
+fn run(command: &str, matches: &ArgMatches) -> Result<(), GraphOperationError> {
+    let input = matches.value_of("INPUT").unwrap_or_default();
+    let format = matches
+        .value_of("FORMAT")
+        .map(str::parse::<GFAVersion>)
+        .transpose()?;
+    let lenient = matches.is_present("LENIENT");
+    if command == "validate" {
+        let (_, graph) = load(input, format, lenient)?;
+        return run_validate(&graph);
+    }
+    // the input file is overwritten only when asked, the standard input goes to the standard output
+    let output = match matches.value_of("OUTPUT") {
+        Some(output) => output,
+        None if input == STDIO || matches.is_present("IN_PLACE") => input,
+        None => {
+            return Err(GraphOperationError::NotSupported(
+                "Saving without --output (or --in-place to overwrite the input file)".to_string(),
+            ))
+        }
+    };
+    let order = match matches.value_of("ORDER") {
+        Some("id") => RecordOrder::Id,
+        Some("topological") => RecordOrder::Topological,
+        _ => RecordOrder::Input,
+    };
+    let path_lines = match matches.value_of("PATH_LINES") {
+        Some("paths") => PathLines::Paths,
+        Some("walks") => PathLines::Walks,
+        _ => PathLines::Input,
+    };
+    let options = SaveOptions {
+        compress: matches.is_present("GZIP"),
+        order,
+        path_lines,
+    };
+    if command == "edit" && input == STDIO && !matches.is_present("SCRIPT") {
+        return Err(GraphOperationError::NotSupported(
+            "Reading the graph of an interactive session from the standard input".to_string(),
+        ));
+    }
+    if command == "convert" {
+        return run_convert(input, output, format, lenient, &options);
+    }
+
+    let (input_version, mut graph) = load(input, format, lenient)?;
+    let output_version = file_version(output, format, input_version);
+    match command {
+        "apply-patch" => {
+            let patch = read_patch_file(value(matches, "PATCH").to_string())?;
+            apply_patch(&mut graph, &patch)?;
+        }
+        "edit" => {
+            let script = match matches.value_of("SCRIPT") {
+                Some(script) => script.to_string(),
+                None => {
+                    // the interactive session saves the graph only when asked
+                    repl::run_repl(
+                        graph,
+                        output,
+                        output_version,
+                        options,
+                        matches.value_of("JOURNAL"),
+                    );
+                    return Ok(());
+                }
+            };
+            let keep_going = matches.is_present("KEEP_GOING");
+            let report = run_script_file(&mut graph, script, keep_going)?;
+            if !keep_going {
+                // the graph has been rolled back (or could not be, as the errors tell), so it is not saved
+                exit_on_errors(&report.errors);
+            }
+            save(&graph, output, output_version, &options)?;
+            exit_on_errors(&report.errors);
+            return Ok(());
+        }
+        _ => match edit_operation(command, matches) {
+            // the paths that walk through the node are changed (or kept) as asked
+            Some(EditOperation::RemoveNode { id }) => {
+                let policy = match matches.value_of("PATHS") {
+                    Some("split") => NodeRemoval::Split,
+                    Some("bridge") => NodeRemoval::Bridge,
+                    _ => NodeRemoval::Refuse,
+                };
+                let report = graph.remove_node_with_paths(&id, policy)?;
+                if report != RemovalReport::default() {
+                    eprintln!("{}", report);
+                }
+            }
+            Some(operation) => {
+                graph.apply(&operation)?;
+            }
+            None => {
+                return Err(GraphOperationError::NotSupported(format!(
+                    "The {} command",
+                    command
+                )))
+            }
+        },
+    }
+    save(&graph, output, output_version, &options)
+}
+
+fn main() {
+    let matches = clap_app!(handlegfa =>
+        (version: "1.0")
+        (author: "Matteo Stievano <m.stievano1@campus.unimib.it>")
+        (about: "This program allows the user to make various operations on a GFA2 (or GFA1) file using instead of a file representation, a graph representation.
+        Every command loads the INPUT file, applies a single operation and saves the result on the OUTPUT file (or overwrites the INPUT file with --in-place).
+        The format of the input (GFA1 or GFA2) is told by its header and its records, the extension (.gfa or .gfa2) is used only when they do not tell it.
+        The format of the output is given by its extension, or it's the same of the input.
+        The file \"-\" stands for the standard input (or output), whose format can be given with --format.
+        The compressed files (.gfa.gz or .gfa2.gz) are recognized and decompressed while they are read.")
+        (setting: clap::AppSettings::SubcommandRequiredElseHelp)
+        (@arg FORMAT: -f --format +takes_value +global "The format (gfa1 or gfa2) of the standard input and output, or of an output without extension")
+        (@arg ORDER: --order +takes_value +global possible_value[input id topological] "The order of the records of the output: the one of the input file (default), ascending id or topological")
+        (@arg PATH_LINES: --("path-lines") +takes_value +global possible_value[input paths walks] "How the paths are written in a GFA1 output: as they have been read (default), as P records or as W records when their haplotype is known")
+        (@arg GZIP: -z --gzip +global "Compress the output with gzip, as it's always done for the files whose name ends with .gz")
+        (@arg LENIENT: --lenient +global "Skip (or repair) the records of the input that cannot be loaded, printing a warning with the line of each one")
+        (@subcommand add_node =>
+            (name: "add-node")
+            (about: "Add a node to the graph")
+            (@arg INPUT: +required "The input GFA file")
+            (@arg ID: +required "The name of the new node")
+            (@arg SEQUENCE: "The sequence of the new node")
+            (@arg OUTPUT: -o --output +takes_value "The output GFA file")
+            (@arg IN_PLACE: --("in-place") conflicts_with[OUTPUT] "Overwrite the input file"))
+        (@subcommand add_link =>
+            (name: "add-link")
+            (about: "Add a link between 2 nodes of the graph")
+            (@arg INPUT: +required "The input GFA file")
+            (@arg FROM: +required "The starting node, with orientation (11+)")
+            (@arg TO: +required "The ending node, with orientation (12-)")
+            (@arg OVERLAP: --overlap +takes_value "The CIGAR of the overlap between the ends of the link (a blunt link when not given)")
+            (@arg OUTPUT: -o --output +takes_value "The output GFA file")
+            (@arg IN_PLACE: --("in-place") conflicts_with[OUTPUT] "Overwrite the input file"))
+        (@subcommand add_path =>
+            (name: "add-path")
+            (about: "Add a path to the graph")
+            (@arg INPUT: +required "The input GFA file")
+            (@arg NAME: +required "The name of the new path")
+            (@arg STEPS: +required ... "The nodes of the path, with orientation (11+ 12- 13+)")
+            (@arg CREATE_LINKS: --("create-links") "Add the links missing between the steps instead of failing")
+            (@arg OUTPUT: -o --output +takes_value "The output GFA file")
+            (@arg IN_PLACE: --("in-place") conflicts_with[OUTPUT] "Overwrite the input file"))
+        (@subcommand remove_node =>
+            (name: "remove-node")
+            (about: "Remove a node, and the links that touch it, from the graph")
+            (@arg INPUT: +required "The input GFA file")
+            (@arg ID: +required "The name of the node")
+            (@arg PATHS: --paths +takes_value possible_value[refuse split bridge] "What to do with the paths that walk through the node: refuse to remove it (default), split them or bridge the gap with a new link")
+            (@arg OUTPUT: -o --output +takes_value "The output GFA file")
+            (@arg IN_PLACE: --("in-place") conflicts_with[OUTPUT] "Overwrite the input file"))
+        (@subcommand remove_link =>
+            (name: "remove-link")
+            (about: "Remove a link from the graph")
+            (@arg INPUT: +required "The input GFA file")
+            (@arg FROM: +required "The starting node, with orientation (11+)")
+            (@arg TO: +required "The ending node, with orientation (12-)")
+            (@arg OUTPUT: -o --output +takes_value "The output GFA file")
+            (@arg IN_PLACE: --("in-place") conflicts_with[OUTPUT] "Overwrite the input file"))
+        (@subcommand remove_path =>
+            (name: "remove-path")
+            (about: "Remove a path from the graph")
+            (@arg INPUT: +required "The input GFA file")
+            (@arg NAME: +required "The name of the path")
+            (@arg OUTPUT: -o --output +takes_value "The output GFA file")
+            (@arg IN_PLACE: --("in-place") conflicts_with[OUTPUT] "Overwrite the input file"))
+        (@subcommand modify_node =>
+            (name: "modify-node")
+            (about: "Change the sequence of a node")
+            (@arg INPUT: +required "The input GFA file")
+            (@arg ID: +required "The name of the node")
+            (@arg SEQUENCE: +required "The new sequence of the node")
+            (@arg OUTPUT: -o --output +takes_value "The output GFA file")
+            (@arg IN_PLACE: --("in-place") conflicts_with[OUTPUT] "Overwrite the input file"))
+        (@subcommand modify_link =>
+            (name: "modify-link")
+            (about: "Move the ends of a link to other nodes")
+            (@arg INPUT: +required "The input GFA file")
+            (@arg FROM: +required "The starting node, with orientation (11+)")
+            (@arg TO: +required "The ending node, with orientation (12-)")
+            (@arg NEW_FROM: --("new-from") +takes_value "The new starting node, with orientation")
+            (@arg NEW_TO: --("new-to") +takes_value "The new ending node, with orientation")
+            (@arg OVERLAP: --overlap +takes_value "The new CIGAR of the overlap (the old one is kept when not given)")
+            (@arg OUTPUT: -o --output +takes_value "The output GFA file")
+            (@arg IN_PLACE: --("in-place") conflicts_with[OUTPUT] "Overwrite the input file"))
+        (@subcommand modify_path =>
+            (name: "modify-path")
+            (about: "Change the nodes of a path")
+            (@arg INPUT: +required "The input GFA file")
+            (@arg NAME: +required "The name of the path")
+            (@arg STEPS: +required ... "The new nodes of the path, with orientation (11+ 12- 13+)")
+            (@arg OUTPUT: -o --output +takes_value "The output GFA file")
+            (@arg IN_PLACE: --("in-place") conflicts_with[OUTPUT] "Overwrite the input file"))
+        (@subcommand apply_patch =>
+            (name: "apply-patch")
+            (about: "Replay a journal of operations on the graph, all together or not at all")
+            (@arg INPUT: +required "The input GFA file")
+            (@arg PATCH: +required "The journal to replay")
+            (@arg OUTPUT: -o --output +takes_value "The output GFA file")
+            (@arg IN_PLACE: --("in-place") conflicts_with[OUTPUT] "Overwrite the input file"))
+        (@subcommand edit =>
+            (about: "Edit the graph with an interactive session, or apply an edit script,
+            one operation per line (ADD NODE 42 ACGT, REMOVE LINK 11+ 13+...) with the same grammar of the interactive session,
+            in a single load and save of the graph")
+            (@arg INPUT: +required "The input GFA file")
+            (@arg SCRIPT: -s --script +takes_value "The edit script to apply, without starting the interactive session")
+            (@arg KEEP_GOING: -k --("keep-going") "Skip the lines that cannot be applied instead of stopping at the first error")
+            (@arg JOURNAL: -j --journal +takes_value "Record the operations applied in the interactive session in a journal FILE")
+            (@arg OUTPUT: -o --output +takes_value "The output GFA file")
+            (@arg IN_PLACE: --("in-place") conflicts_with[OUTPUT] "Overwrite the input file"))
+        (@subcommand convert =>
+            (about: "Convert a GFA1 file into a GFA2 file, or vice versa, into the version given by the extension of the output (or by --format).
+            Every record that cannot be represented in the output (containments, fragments, gaps, groups, overlaps...) is reported on the standard error")
+            (@arg INPUT: +required "The input GFA file")
+            (@arg OUTPUT: +required "The output GFA file"))
+        (@subcommand validate =>
+            (about: "Check that the graph is coherent: every problem (edges and path steps on nodes that do not exist,
+            consecutive path steps not joined by an edge, path names used more than once) is printed and the exit code is 8")
+            (@arg INPUT: +required "The input GFA file"))
+    )
+    .get_matches();
+
+    if let Some((command, matches)) = matches.subcommand() {
+        if let Err(why) = run(command, matches) {
+            exit_on_errors(&[why]);
+        }
+    }
+}
@@ -0,0 +1,390 @@
+/// interactive session to edit a graph one command at a time
+use handlegfa::fileoperation::*;
+use handlegfa::graphoperation::*;
+
+use bstr::ByteSlice;
+use handlegraph2::handlegraph::HandleGraph;
+use rustyline::{
+    completion::Completer, error::ReadlineError, highlight::Highlighter, hint::Hinter,
+    validate::Validator, Context, Editor, Helper,
+};
+
+use crate::{file_version, save};
+
+const WELCOME_MESSAGE: &str = "Type a command to edit the graph (case insensitive), \
+[HELP] to list the commands or [HELP COMMAND] to know how to use one of them.\n\
+Use the arrow keys to move through the commands already typed and [TAB] to complete them.";
+
+// every command with its usage
+const COMMANDS: [(&str, &str); 11] = [
+    (
+        "ADD",
+        "ADD NODE [NODEID] [SEQUENCE|*] [LENGTH]\n\
+        ADD LINK [FROM NODEID(+-)] [TO NODEID(+-)] [OVERLAP]\n\
+        ADD EDGE [EDGEID|*] [FROM NODEID(+-)] [TO NODEID(+-)] [BEG1] [END1] [BEG2] [END2] [ALIGNMENT]\n\
+        ADD PATH [PATH_NAME] [NODEID(+-)]... [CREATE-LINKS|UNCHECKED]\n\
+        ADD CONTAINMENT [CONTAINER NODEID(+-)] [CONTAINED NODEID(+-)] [POS] [OVERLAP] [TAG:TYPE:VALUE]...\n\
+        ADD FRAGMENT [NODEID] [EXTERNAL(+-)] [SBEG] [SEND] [FBEG] [FEND] [ALIGNMENT] [TAG:TYPE:VALUE]...\n\
+        ADD GAP [GAPID|*] [FROM NODEID(+-)] [TO NODEID(+-)] [DISTANCE] [VARIANCE|*] [TAG:TYPE:VALUE]...\n\
+        ADD GROUP [GROUPID] [MEMBER]... [TAG:TYPE:VALUE]...\n\
+        Add a node, a link, a path, a containment, a fragment, a gap or an unordered group to the graph. \
+        The character \"*\" represent that the sequence it's not provided. \
+        The overlap of a link is a CIGAR string, a blunt link is added when it's not provided. \
+        An edge is a link with the positions of its ends, as written in a GFA2 file. \
+        The consecutive steps of a path must be joined by a link: with CREATE-LINKS the missing links are added \
+        together with the path, with UNCHECKED they are not checked",
+    ),
+    (
+        "REMOVE",
+        "REMOVE NODE [NODEID]\n\
+        REMOVE LINK [FROM NODEID(+-)] [TO NODEID(+-)]\n\
+        REMOVE PATH [PATH_NAME]\n\
+        REMOVE CONTAINMENT [CONTAINER NODEID(+-)] [CONTAINED NODEID(+-)]\n\
+        REMOVE WALK [PATH_NAME]\n\
+        REMOVE FRAGMENT [NODEID] [EXTERNAL(+-)] [SBEG SEND FBEG FEND]\n\
+        REMOVE GAP [GAPID|FROM NODEID(+-) TO NODEID(+-)]\n\
+        REMOVE GROUP [GROUPID]\n\
+        REMOVE TAG [NODE NODEID|LINK FROM TO|PATH PATH_NAME] [TAG]\n\
+        Remove a node (with the links, containments, fragments and gaps that touch it), a link, a path, \
+        a containment, the haplotype of a path (written again as a P record), \
+        a fragment, a gap, an unordered group or an optional field from the graph",
+    ),
+    (
+        "MODIFY",
+        "MODIFY NODE [NODEID] [SEQUENCE]\n\
+        MODIFY LINK [FROM NODEID(+-)] [TO NODEID(+-)] [NEW FROM NODEID(+-)|*] [NEW TO NODEID(+-)|*] [OVERLAP]\n\
+        MODIFY PATH [PATH_NAME] [NODEID(+-)]...\n\
+        MODIFY GROUP [GROUPID] [MEMBER]...\n\
+        Change the sequence of a node, the ends of a link, the nodes of a path or the members of a group. \
+        The character \"*\" keeps the old end of the link, the old overlap is kept when a new one is not provided",
+    ),
+    (
+        "SET",
+        "SET TAG [NODE NODEID|LINK FROM TO|PATH PATH_NAME] [TAG:TYPE:VALUE]\n\
+        SET WALK [PATH_NAME] [SAMPLE] [HAPLOTYPE] [SEQ_ID] [START|*] [END|*]\n\
+        Add an optional field to an element of the graph, or change its value. \
+        Set the haplotype of a path, that's written as a W record in GFA1. \
+        A value with spaces is written between double quotes, as in SET TAG NODE 11 \"CO:Z:two words\"",
+    ),
+    ("UNDO", "UNDO\nTake back the last operation"),
+    ("REDO", "REDO\nApply again the last operation taken back"),
+    ("HISTORY", "HISTORY\nList the operations applied"),
+    ("SHOW", "SHOW\nPrint the graph"),
+    (
+        "SAVE",
+        "SAVE [FILE]\nSave the graph on FILE (.gfa or .gfa2, otherwise in the format of the output file), or on the output file if not provided",
+    ),
+    (
+        "HELP",
+        "HELP [COMMAND]\nList the commands, or show how to use one of them",
+    ),
+    (
+        "QUIT",
+        "QUIT\nEnd the session. If there are changes not saved it must be typed twice",
+    ),
+];
+
+// the second word of the commands that need it
+const ELEMENTS: [&str; 9] = [
+    "NODE",
+    "LINK",
+    "PATH",
+    "CONTAINMENT",
+    "FRAGMENT",
+    "GAP",
+    "GROUP",
+    "WALK",
+    "TAG",
+];
+
+fn help(command: Option<&str>) -> String {
+    match command {
+        None => {
+            let commands: Vec<&str> = COMMANDS.iter().map(|(command, _)| *command).collect();
+            format!("The possible commands are: {}", commands.join(", "))
+        }
+        Some(command) => match COMMANDS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(command))
+        {
+            Some((_, usage)) => usage.to_string(),
+            None => format!("No command {}, type HELP to list them", command),
+        },
+    }
+}
+
+/// Complete the commands, the names of the nodes and the names of the paths of the graph
+#[derive(Debug, Default)]
+pub struct GraphHelper {
+    nodes: Vec<String>,
+    paths: Vec<String>,
+}
+
+impl GraphHelper {
+    pub fn new(graph: &GFAGraph) -> Self {
+        let mut helper = GraphHelper::default();
+        helper.update(graph);
+        helper
+    }
+
+    /// Read again the names of the nodes and paths, after the graph has been edited
+    pub fn update(&mut self, graph: &GFAGraph) {
+        self.nodes = graph
+            .graph
+            .handles_iter()
+            .map(|handle| graph.node_name(handle.id()).to_string())
+            .collect();
+        self.nodes.sort();
+        self.paths = graph
+            .graph
+            .paths
+            .values()
+            .map(|path| path.name.to_str_lossy().to_string())
+            .collect();
+        self.paths.sort();
+    }
+
+    // the words that can follow the ones already typed
+    fn candidates(&self, typed: &[&str]) -> Vec<String> {
+        let keyword = |word: &str| word.to_ascii_uppercase();
+        match typed {
+            [] => COMMANDS
+                .iter()
+                .map(|(command, _)| command.to_string())
+                .collect(),
+            [command] if keyword(command) == "HELP" => COMMANDS
+                .iter()
+                .map(|(command, _)| command.to_string())
+                .collect(),
+            [command] if keyword(command) == "SET" => vec!["TAG".to_string(), "WALK".to_string()],
+            [command] if keyword(command) == "ADD" => ELEMENTS[..7]
+                .iter()
+                .map(|element| element.to_string())
+                .collect(),
+            [command] if keyword(command) == "MODIFY" => ["NODE", "LINK", "PATH", "GROUP"]
+                .iter()
+                .map(|element| element.to_string())
+                .collect(),
+            [command] if keyword(command) == "REMOVE" => {
+                ELEMENTS.iter().map(|element| element.to_string()).collect()
+            }
+            // the name of a path is the first argument of a path command
+            [_, element] if ["PATH", "WALK"].contains(&keyword(element).as_str()) => {
+                self.paths.clone()
+            }
+            [_, _, element] if keyword(element) == "PATH" => self.paths.clone(),
+            [_, element] if keyword(element) == "TAG" => ELEMENTS[..3]
+                .iter()
+                .map(|element| element.to_string())
+                .collect(),
+            _ => self.nodes.clone(),
+        }
+    }
+}
+
+impl Completer for GraphHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .rfind(char::is_whitespace)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let typed: Vec<&str> = line[..start].split_whitespace().collect();
+        let word = &line[start..pos];
+        let candidates = self
+            .candidates(&typed)
+            .into_iter()
+            .filter(|candidate| {
+                candidate.starts_with(word)
+                    || candidate.starts_with(word.to_ascii_uppercase().as_str())
+            })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for GraphHelper {
+    type Hint = String;
+}
+
+impl Highlighter for GraphHelper {}
+
+impl Validator for GraphHelper {}
+
+impl Helper for GraphHelper {}
+
+// the state of the interactive session
+struct Session {
+    graph: GFAGraph,
+    history: History,
+    output: String,
+    version: GFAVersion,
+    options: SaveOptions,
+    changed: bool,
+    quitting: bool,
+}
+
+impl Session {
+    fn print_history(&self) {
+        if self.history.is_empty() {
+            println!("No operation applied");
+        }
+        for (i, entry) in self.history.iter().enumerate() {
+            for operation in entry.operations.iter() {
+                println!("{}. {}", i + 1, operation);
+            }
+        }
+        for entry in self.history.undone() {
+            for operation in entry.operations.iter() {
+                println!("(undone) {}", operation);
+            }
+        }
+    }
+
+    // execute a command, returning true when the session ends
+    fn execute(&mut self, line: &str) -> Result<bool, GraphOperationError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let command = words[0].to_ascii_uppercase();
+        // a QUIT not followed by another one is forgotten
+        let quitting = self.quitting;
+        self.quitting = false;
+
+        match command.as_str() {
+            "QUIT" | "EXIT" | "STOP" => {
+                if self.changed && !quitting {
+                    println!("There are changes not saved: type SAVE to save them or QUIT again to discard them");
+                    self.quitting = true;
+                    return Ok(false);
+                }
+                return Ok(true);
+            }
+            "HELP" => println!("{}", help(words.get(1).copied())),
+            "SHOW" => print_simple_graph(&self.graph.graph),
+            "HISTORY" => self.print_history(),
+            "SAVE" => {
+                let file = words.get(1).copied().unwrap_or(&self.output).to_string();
+                save(
+                    &self.graph,
+                    &file,
+                    file_version(&file, None, self.version),
+                    &self.options,
+                )?;
+                self.changed = false;
+                println!("File saved in {}", file);
+            }
+            "UNDO" => match self.history.undo(&mut self.graph)? {
+                Some(entry) => {
+                    println!("Undone: {}", entry.operations[0]);
+                    self.changed = true;
+                }
+                None => println!("No operation to undo"),
+            },
+            "REDO" => match self.history.redo(&mut self.graph)? {
+                Some(entry) => {
+                    println!("Redone: {}", entry.operations[0]);
+                    self.changed = true;
+                }
+                None => println!("No operation to redo"),
+            },
+            _ => {
+                let operation = line.parse::<EditOperation>()?;
+                self.history.apply(&mut self.graph, operation.clone())?;
+                self.changed = true;
+                println!("Done: {}", operation);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Edit a graph with an interactive session. The errors are printed and never end the session.\
+/// At the end the operations applied are saved in the journal, if provided
+pub fn run_repl(
+    graph: GFAGraph,
+    output: &str,
+    version: GFAVersion,
+    options: SaveOptions,
+    journal: Option<&str>,
+) {
+    let mut editor = Editor::<GraphHelper>::new();
+    editor.set_helper(Some(GraphHelper::new(&graph)));
+    let mut session = Session {
+        graph,
+        history: History::new(),
+        output: output.to_string(),
+        version,
+        options,
+        changed: false,
+        quitting: false,
+    };
+
+    println!("{}", WELCOME_MESSAGE);
+    loop {
+        match editor.readline("handlegfa> ") {
+            Ok(line) => {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                editor.add_history_entry(line);
+                match session.execute(line) {
+                    Ok(true) => break,
+                    Ok(false) => (),
+                    Err(GraphOperationError::InvalidOperation(command)) => {
+                        println!("Command not recognized: {}\n{}", command, help(None))
+                    }
+                    Err(why) => println!("Error: {}", why),
+                }
+                if let Some(helper) = editor.helper_mut() {
+                    helper.update(&session.graph);
+                }
+            }
+            Err(ReadlineError::Interrupted) => println!("Type QUIT to end the session"),
+            Err(ReadlineError::Eof) => {
+                if session.changed {
+                    println!("Changes not saved!");
+                }
+                break;
+            }
+            Err(why) => {
+                println!("Error: {}", why);
+                break;
+            }
+        }
+    }
+
+    if let Some(journal) = journal {
+        match save_patch_file(&session.history.patch(), Some(journal.to_string())) {
+            Ok(_) => println!("Journal saved in {}", journal),
+            Err(why) => println!("Error: {}", why),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_complete_names_and_commands() {
+        let graph = gfa2_to_handlegraph("./tests/gfa2_files/spec_q7.gfa2".to_string()).unwrap();
+        let helper = GraphHelper::new(&graph);
+
+        assert_eq!(helper.candidates(&[]).len(), COMMANDS.len());
+        assert_eq!(helper.candidates(&["remove"]), ELEMENTS);
+        assert_eq!(
+            helper.candidates(&["modify"]),
+            ["NODE", "LINK", "PATH", "GROUP"]
+        );
+        assert_eq!(helper.candidates(&["ADD", "LINK"]), ["11", "12", "13"]);
+        assert_eq!(helper.candidates(&["modify", "path"]), ["14", "15"]);
+        assert_eq!(helper.candidates(&["SET", "TAG", "PATH"]), ["14", "15"]);
+        assert!(help(Some("modify")).starts_with("MODIFY NODE"));
+    }
+}
@@ -2,7 +2,7 @@
 use handlegraph2::hashgraph::*;
 
 // manipulate files
-use gfa2::{gfa1::GFA, gfa2::GFA2, parser_gfa1::GFAParser, parser_gfa2::GFA2Parser};
+use gfa2::{gfa2::GFA2, parser_gfa2::GFA2Parser};
 
 use handlegfa::{fileoperation::*, graphoperation::*};
 
@@ -17,19 +17,17 @@ fn readme_file_test() {
     print_simple_graph(&graph);
 }
 
-// about 20 seconds
+// the graph is edited in place, without cloning it at every operation
 #[test]
 fn moddable_medium_graph() {
     println!("Parse and create graph");
-    let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
-    let gfa2: GFA2<usize, ()> = parser.parse_file("./tests/big_files/test.gfa2").unwrap();
-    let mut graph = HashGraph::from_gfa2(&gfa2);
+    let mut graph = gfa2_to_handlegraph("./tests/big_files/test.gfa2".to_string()).unwrap();
 
     // remove nodes, edges and paths
     println!("Remove 1000 nodes");
     for i in 1..1001 {
-        match remove_node(graph.clone(), i as u64) {
-            Ok(g) => graph = g,
+        match graph.remove_node(i.to_string().as_bytes()) {
+            Ok(_) => (),
             Err(why) => println!("Error: {}", why),
         };
     }
@@ -41,30 +39,30 @@ fn moddable_medium_graph() {
     println!("Remove 3 paths");
     for i in 1..PATHS.len() {
         let path_name: &[u8] = PATHS.get(i as usize).unwrap();
-        match remove_path(graph.clone(), Some(path_name)) {
-            Ok(g) => graph = g,
+        match graph.remove_path(Some(path_name)) {
+            Ok(_) => (),
             Err(why) => println!("Error: {}", why),
         };
     }
     println!("Remove 5 edges");
-    match remove_link(graph.clone(), b"2138-", b"2137-") {
-        Ok(g) => graph = g,
+    match graph.remove_link(b"2138-", b"2137-") {
+        Ok(_) => (),
         Err(why) => println!("Error: {}", why),
     };
-    match remove_link(graph.clone(), b"2139+", b"2140+") {
-        Ok(g) => graph = g,
+    match graph.remove_link(b"2139+", b"2140+") {
+        Ok(_) => (),
         Err(why) => println!("Error: {}", why),
     };
-    match remove_link(graph.clone(), b"2139+", b"3090+") {
-        Ok(g) => graph = g,
+    match graph.remove_link(b"2139+", b"3090+") {
+        Ok(_) => (),
         Err(why) => println!("Error: {}", why),
     };
-    match remove_link(graph.clone(), b"2139-", b"2138-") {
-        Ok(g) => graph = g,
+    match graph.remove_link(b"2139-", b"2138-") {
+        Ok(_) => (),
         Err(why) => println!("Error: {}", why),
     };
-    match remove_link(graph.clone(), b"2140+", b"2141+") {
-        Ok(g) => graph = g,
+    match graph.remove_link(b"2140+", b"2141+") {
+        Ok(_) => (),
         Err(why) => println!("Error: {}", why),
     };
 
@@ -75,22 +73,22 @@ fn moddable_medium_graph() {
         b"5010-",
     ];
     for i in 1..11 {
-        match add_node(graph.clone(), 5000 + i as u64, None) {
-            Ok(g) => graph = g,
+        match graph.add_node((5000 + i).to_string().as_bytes(), None) {
+            Ok(_) => (),
             Err(why) => println!("Error: {}", why),
         };
         if i > 1 {
-            match add_link_between_nodes(
-                graph.clone(),
+            match graph.add_link_between_nodes(
                 format!("{}{}", 4000 + i - 1 as u64, "+".to_string()).as_bytes(),
                 format!("{}{}", 4000 + i as u64, "+".to_string()).as_bytes(),
+                None,
             ) {
-                Ok(g) => graph = g,
+                Ok(_) => (),
                 Err(why) => println!("Error: {}", why),
             };
         }
     }
-    match add_path(graph.clone(), None, paths.clone()) {
+    match graph.add_path(None, paths.clone()) {
         Ok(_) => (),
         Err(why) => println!("Error: {}", why),
     };
@@ -101,21 +99,14 @@ fn moddable_medium_graph() {
 fn big_graph_with_big_operation() {
     // about 8 minutes
     println!("Parse and create graph");
-    let parser: GFAParser<usize, ()> = GFAParser::new();
-    let gfa: GFA<usize, ()> = parser
-        .parse_file("./tests/big_files/ape-4-0.10b.gfa")
-        .unwrap();
-    let mut graph = HashGraph::from_gfa(&gfa);
+    let mut graph = gfa1_to_handlegraph("./tests/big_files/ape-4-0.10b.gfa".to_string()).unwrap();
 
     // about x minutes
     // remove nodes
     println!("Remove 1_000 nodes");
     for i in 1..1_001 {
-        match remove_node(
-            graph.clone(),
-            format!("{}{}", 115, i).parse::<u64>().unwrap(),
-        ) {
-            Ok(g) => graph = g,
+        match graph.remove_node(format!("{}{}", 115, i).as_bytes()) {
+            Ok(_) => (),
             Err(why) => println!("Error: {}", why),
         };
     }
@@ -157,8 +148,8 @@ fn big_graph_with_big_operation() {
 fn extension_error() {
     match gfa2_to_handlegraph("./tests/gfa2_files/error_extension.txt".to_string()) {
         Ok(g) => {
-            let graph: HashGraph = g;
-            print_simple_graph(&graph)
+            let graph: GFAGraph = g;
+            print_simple_graph(&graph.graph)
         }
         Err(why) => println!("Error: {}", why),
     };